@@ -0,0 +1,98 @@
+use std::{
+    io::{self, ErrorKind},
+    mem::size_of,
+    str,
+};
+
+use anyhow::Result;
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+
+/// The magic value of the embedded build information.
+pub const BUILD_INFO_MAGIC: &[u8; 4] = b"SBI0";
+
+/// Information about the build that produced a Kernel Image.
+///
+/// When supplied to the builder, this is embedded right after
+/// the Kernel Loader so that the sources of a flashed image can
+/// be identified from the binary alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The git commit the image was built from.
+    pub commit: String,
+    /// The Unix timestamp of the build, in seconds.
+    pub timestamp: u64,
+    /// The version of the tooling that built the image.
+    pub builder_version: String,
+}
+
+impl BuildInfo {
+    const HEADER_SIZE: usize = size_of::<u32>() * 4 + size_of::<u64>();
+
+    /// Deserializes build information from a given reader.
+    pub fn read(mut data: &[u8]) -> io::Result<Self> {
+        if data.read_u32::<LE>()? != u32::from_le_bytes(*BUILD_INFO_MAGIC) {
+            return Err(invalid_data("build info magic mismatch"));
+        }
+
+        let size = data.read_u32::<LE>()? as usize;
+        let timestamp = data.read_u64::<LE>()?;
+        let commit_len = data.read_u32::<LE>()? as usize;
+        let builder_version_len = data.read_u32::<LE>()? as usize;
+
+        if size != Self::HEADER_SIZE + commit_len + builder_version_len {
+            return Err(invalid_data("build info size mismatch"));
+        }
+
+        Ok(Self {
+            commit: read_str(&mut data, commit_len)?,
+            timestamp,
+            builder_version: read_str(&mut data, builder_version_len)?,
+        })
+    }
+
+    /// Gets the binary size of the build information.
+    pub fn size(&self) -> usize {
+        Self::HEADER_SIZE + self.commit.len() + self.builder_version.len()
+    }
+
+    /// Serializes the build information to a given writer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(BUILD_INFO_MAGIC)?;
+        writer.write_u32::<LE>(self.size() as u32)?;
+        writer.write_u64::<LE>(self.timestamp)?;
+        writer.write_u32::<LE>(self.commit.len() as u32)?;
+        writer.write_u32::<LE>(self.builder_version.len() as u32)?;
+        writer.write_all(self.commit.as_bytes())?;
+        writer.write_all(self.builder_version.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// Parses the [`BuildInfo`] at `offset` in a Kernel Image, if any.
+///
+/// The build info is placed right after the Kernel Loader, so
+/// `offset` is the end of the loader as recorded in its metadata.
+pub(crate) fn read_build_info_at(image: &[u8], offset: usize) -> Result<Option<BuildInfo>> {
+    match image.get(offset..) {
+        Some(info) if info.starts_with(BUILD_INFO_MAGIC) => Ok(Some(BuildInfo::read(info)?)),
+        _ => Ok(None),
+    }
+}
+
+fn read_str(data: &mut &[u8], len: usize) -> io::Result<String> {
+    if data.len() < len {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+
+    let (s, rest) = data.split_at(len);
+    *data = rest;
+
+    str::from_utf8(s)
+        .map(ToOwned::to_owned)
+        .map_err(|_| invalid_data("build info string is not valid UTF-8"))
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
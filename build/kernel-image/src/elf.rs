@@ -0,0 +1,204 @@
+use std::io;
+
+use byteorder::{WriteBytesExt, LE};
+
+const EHDR_SIZE: usize = 0x40;
+const PHDR_SIZE: usize = 0x38;
+const SHDR_SIZE: usize = 0x40;
+const SYM_SIZE: usize = 0x18;
+
+const ET_EXEC: u16 = 2;
+const EM_AARCH64: u16 = 183;
+const PT_LOAD: u32 = 1;
+const PF_RWX: u32 = 0b111;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHF_WRITE_ALLOC_EXEC: u64 = 0b111;
+
+const STB_GLOBAL_NOTYPE: u8 = 1 << 4;
+
+// Section indices in the order they are written.
+const IMAGE_SHNDX: u16 = 1;
+const STRTAB_SHNDX: u32 = 3;
+const SHSTRTAB_SHNDX: u16 = 4;
+
+struct SectionHeader {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    addr: u64,
+    offset: usize,
+    size: usize,
+    link: u32,
+    info: u32,
+    align: u64,
+    entsize: u64,
+}
+
+impl SectionHeader {
+    fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u32::<LE>(self.name)?;
+        writer.write_u32::<LE>(self.kind)?;
+        writer.write_u64::<LE>(self.flags)?;
+        writer.write_u64::<LE>(self.addr)?;
+        writer.write_u64::<LE>(self.offset as u64)?;
+        writer.write_u64::<LE>(self.size as u64)?;
+        writer.write_u32::<LE>(self.link)?;
+        writer.write_u32::<LE>(self.info)?;
+        writer.write_u64::<LE>(self.align)?;
+        writer.write_u64::<LE>(self.entsize)?;
+
+        Ok(())
+    }
+}
+
+// Builds a string table from the given names and returns it along
+// with the offsets of every name in it.
+fn build_strtab<'a>(names: impl Iterator<Item = &'a str>) -> (Vec<u8>, Vec<u32>) {
+    let mut table = vec![0];
+    let offsets = names
+        .map(|name| {
+            let offset = table.len() as u32;
+            table.extend(name.as_bytes());
+            table.push(0);
+            offset
+        })
+        .collect();
+
+    (table, offsets)
+}
+
+/// Wraps a flat `image` into a minimal AArch64 ELF file.
+///
+/// The image becomes a single `PT_LOAD` segment at `load_address`
+/// which is also the entrypoint. Every symbol is given as a name
+/// and an offset into the image.
+pub(crate) fn write_elf<W: io::Write>(
+    writer: &mut W,
+    image: &[u8],
+    load_address: u64,
+    align: usize,
+    symbols: &[(&str, usize)],
+) -> io::Result<()> {
+    let (strtab, name_offsets) = build_strtab(symbols.iter().map(|(name, _)| *name));
+    let (shstrtab, section_names) =
+        build_strtab([".image", ".symtab", ".strtab", ".shstrtab"].into_iter());
+
+    // Compute the file layout. The image must be placed at an
+    // offset congruent to its load address modulo the alignment.
+    let image_offset = crate::align_up(EHDR_SIZE + PHDR_SIZE, align);
+    let symtab_offset = crate::align_up(image_offset + image.len(), 8);
+    let symtab_size = (symbols.len() + 1) * SYM_SIZE;
+    let strtab_offset = symtab_offset + symtab_size;
+    let shstrtab_offset = strtab_offset + strtab.len();
+    let shdr_offset = crate::align_up(shstrtab_offset + shstrtab.len(), 8);
+
+    let mut elf = Vec::with_capacity(shdr_offset + 5 * SHDR_SIZE);
+
+    // Write the ELF header.
+    elf.extend(b"\x7FELF\x02\x01\x01");
+    elf.resize(16, 0);
+    elf.write_u16::<LE>(ET_EXEC)?;
+    elf.write_u16::<LE>(EM_AARCH64)?;
+    elf.write_u32::<LE>(1)?;
+    elf.write_u64::<LE>(load_address)?;
+    elf.write_u64::<LE>(EHDR_SIZE as u64)?;
+    elf.write_u64::<LE>(shdr_offset as u64)?;
+    elf.write_u32::<LE>(0)?;
+    elf.write_u16::<LE>(EHDR_SIZE as u16)?;
+    elf.write_u16::<LE>(PHDR_SIZE as u16)?;
+    elf.write_u16::<LE>(1)?;
+    elf.write_u16::<LE>(SHDR_SIZE as u16)?;
+    elf.write_u16::<LE>(5)?;
+    elf.write_u16::<LE>(SHSTRTAB_SHNDX)?;
+
+    // Write the program header for the image segment.
+    elf.write_u32::<LE>(PT_LOAD)?;
+    elf.write_u32::<LE>(PF_RWX)?;
+    elf.write_u64::<LE>(image_offset as u64)?;
+    elf.write_u64::<LE>(load_address)?;
+    elf.write_u64::<LE>(load_address)?;
+    elf.write_u64::<LE>(image.len() as u64)?;
+    elf.write_u64::<LE>(image.len() as u64)?;
+    elf.write_u64::<LE>(align as u64)?;
+
+    // Write the image itself.
+    elf.resize(image_offset, 0);
+    elf.extend(image);
+
+    // Write the symbol table, starting with the null symbol.
+    elf.resize(symtab_offset + SYM_SIZE, 0);
+    for (&(_, offset), name) in symbols.iter().zip(name_offsets) {
+        elf.write_u32::<LE>(name)?;
+        elf.write_u8(STB_GLOBAL_NOTYPE)?;
+        elf.write_u8(0)?;
+        elf.write_u16::<LE>(IMAGE_SHNDX)?;
+        elf.write_u64::<LE>(load_address + offset as u64)?;
+        elf.write_u64::<LE>(0)?;
+    }
+
+    // Write the string tables.
+    elf.extend(&strtab);
+    elf.extend(&shstrtab);
+
+    // Write the section headers, starting with the null section.
+    elf.resize(shdr_offset + SHDR_SIZE, 0);
+    let sections = [
+        SectionHeader {
+            name: section_names[0],
+            kind: SHT_PROGBITS,
+            flags: SHF_WRITE_ALLOC_EXEC,
+            addr: load_address,
+            offset: image_offset,
+            size: image.len(),
+            link: 0,
+            info: 0,
+            align: align as u64,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: section_names[1],
+            kind: SHT_SYMTAB,
+            flags: 0,
+            addr: 0,
+            offset: symtab_offset,
+            size: symtab_size,
+            link: STRTAB_SHNDX,
+            // Index of the first non-local symbol.
+            info: 1,
+            align: 8,
+            entsize: SYM_SIZE as u64,
+        },
+        SectionHeader {
+            name: section_names[2],
+            kind: SHT_STRTAB,
+            flags: 0,
+            addr: 0,
+            offset: strtab_offset,
+            size: strtab.len(),
+            link: 0,
+            info: 0,
+            align: 1,
+            entsize: 0,
+        },
+        SectionHeader {
+            name: section_names[3],
+            kind: SHT_STRTAB,
+            flags: 0,
+            addr: 0,
+            offset: shstrtab_offset,
+            size: shstrtab.len(),
+            link: 0,
+            info: 0,
+            align: 1,
+            entsize: 0,
+        },
+    ];
+    for section in &sections {
+        section.write(&mut elf)?;
+    }
+
+    writer.write_all(&elf)
+}
@@ -1,32 +1,178 @@
-use std::mem;
+use std::{io, mem::size_of};
 
-use byteorder::{ByteOrder, LE};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, LE};
 
 /// The maximum number of KIPs a INI1 record can store.
 pub const MAX_KIP_COUNT: u8 = 0x50;
 
+/// The default maximum size of an INI1 record in bytes, including
+/// its header.
+///
+/// Larger records are not guaranteed to fit into the memory the
+/// Kernel Loader sets aside for them at boot.
+pub const MAX_INI1_SIZE: usize = 12 << 20;
+
 /// The header magic of a KIP binary.
 pub const KIP_MAGIC: &[u8] = b"KIP1";
 
-const INI1_MAGIC: u32 = u32::from_le_bytes(*b"INI1");
+/// The size of a KIP binary header.
+pub const KIP_HEADER_SIZE: usize = 0x100;
+
+/// The magic value of an INI1 record.
+pub const INI1_MAGIC: &[u8; 4] = b"INI1";
 
 /// The header of an INI1 record.
-pub type Ini1Header = [u8; 16];
+///
+/// This must be kept in sync with the layout the Kernel reads
+/// the record of Kernel Initial Processes from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct Ini1Header {
+    /// The 4 bytes INI1 magic value.
+    pub magic: u32,
+    /// The size of the whole record, including this header.
+    pub size: u32,
+    /// The number of KIPs in the record.
+    pub num_processes: u32,
+    /// Reserved; always zero.
+    pub reserved: u32,
+}
+
+const _: () = assert!(size_of::<Ini1Header>() == 0x10);
+
+impl Ini1Header {
+    /// Creates the header for a record of `kip_count` KIPs which
+    /// take up `kip_bytes` in total.
+    pub fn new(kip_bytes: usize, kip_count: u8) -> Self {
+        Self {
+            magic: u32::from_le_bytes(*INI1_MAGIC),
+            size: (kip_bytes + size_of::<Self>()) as u32,
+            num_processes: kip_count as u32,
+            reserved: 0,
+        }
+    }
+
+    /// Deserializes a header from a given reader.
+    pub fn read(mut data: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            magic: data.read_u32::<LE>()?,
+            size: data.read_u32::<LE>()?,
+            num_processes: data.read_u32::<LE>()?,
+            reserved: data.read_u32::<LE>()?,
+        })
+    }
+
+    /// Gets the binary size of the header.
+    pub fn size(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    /// Serializes the header to a given writer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u32::<LE>(self.magic)?;
+        writer.write_u32::<LE>(self.size)?;
+        writer.write_u32::<LE>(self.num_processes)?;
+        writer.write_u32::<LE>(self.reserved)?;
+
+        Ok(())
+    }
+
+    /// Serializes the header into its raw byte representation.
+    pub fn to_bytes(&self) -> [u8; size_of::<Self>()] {
+        let mut bytes = [0; size_of::<Self>()];
+        self.write(&mut &mut bytes[..]).unwrap();
+        bytes
+    }
+}
 
 /// Builds the INI1 header for a record of KIPs, if necessary.
 pub fn build_ini1_header(kip_bytes: usize, kip_count: u8) -> Option<Ini1Header> {
-    if kip_count == 0 {
+    (kip_count != 0).then(|| Ini1Header::new(kip_bytes, kip_count))
+}
+
+/// Information about a single KIP in an INI1 record.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KipEntry {
+    /// The process name from the KIP header.
+    pub name: String,
+    /// The total size of the KIP binary in bytes.
+    pub size: usize,
+    /// The process flags from the KIP header.
+    pub flags: u8,
+    /// The offset of the KIP binary within the INI1 record.
+    pub offset: usize,
+}
+
+impl KipEntry {
+    /// Parses the header of the KIP binary at the start of `kip`,
+    /// which is located at `offset` within its INI1 record.
+    ///
+    /// Returns [`None`] if `kip` does not start with a valid header.
+    pub fn parse(kip: &[u8], offset: usize) -> Option<Self> {
+        let size = kip_size(kip)?;
+        let name = kip_name(kip)?;
+
+        Some(Self {
+            name,
+            size,
+            flags: kip[0x1F],
+            offset,
+        })
+    }
+}
+
+/// Extracts the process name from the header of a KIP binary.
+///
+/// Returns [`None`] if `kip` does not start with a valid header.
+pub fn kip_name(kip: &[u8]) -> Option<String> {
+    if kip.len() < KIP_HEADER_SIZE || &kip[..KIP_MAGIC.len()] != KIP_MAGIC {
+        return None;
+    }
+
+    // The name is a NUL-padded string of 12 bytes at 0x4.
+    let name = &kip[0x4..0x10];
+    let name = match name.iter().position(|&b| b == 0) {
+        Some(len) => &name[..len],
+        None => name,
+    };
+
+    Some(String::from_utf8_lossy(name).into_owned())
+}
+
+/// Calculates the total size of a KIP binary from its header.
+///
+/// Returns [`None`] if `kip` does not start with a valid header.
+pub fn kip_size(kip: &[u8]) -> Option<usize> {
+    if kip.len() < KIP_HEADER_SIZE || &kip[..KIP_MAGIC.len()] != KIP_MAGIC {
         return None;
     }
 
-    let mut header = [0; 16];
+    // The .text, .rodata and .data segment headers are stored at
+    // 0x20 and each hold their compressed file size at offset 8.
+    let segments = (0..3).map(|i| LE::read_u32(&kip[0x20 + i * 0x10 + 8..]) as usize);
+    Some(KIP_HEADER_SIZE + segments.sum::<usize>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    LE::write_u32(&mut header, INI1_MAGIC);
-    LE::write_u32(
-        &mut header[4..],
-        (kip_bytes + mem::size_of::<Ini1Header>()) as u32,
-    );
-    LE::write_u32(&mut header[8..], kip_count as u32);
+    #[test]
+    fn ini1_header_layout() {
+        let header = build_ini1_header(0x1234, 3).unwrap();
+        let bytes = header.to_bytes();
 
-    Some(header)
+        assert_eq!(&bytes[0x0..0x4], INI1_MAGIC);
+        assert_eq!(bytes[0x4..0x8], (0x1234u32 + 0x10).to_le_bytes());
+        assert_eq!(bytes[0x8..0xC], 3u32.to_le_bytes());
+        assert_eq!(bytes[0xC..0x10], [0; 4]);
+
+        assert_eq!(Ini1Header::read(&bytes).unwrap(), header);
+    }
+
+    #[test]
+    fn no_ini1_header_without_kips() {
+        assert_eq!(build_ini1_header(0, 0), None);
+        assert_eq!(build_ini1_header(0x1000, 0), None);
+    }
 }
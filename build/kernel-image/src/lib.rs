@@ -9,21 +9,41 @@
 
 use std::{
     fs,
-    io::{Seek, SeekFrom, Write},
-    iter,
-    path::Path,
+    io::{self, Read, Seek, Write},
+    mem::size_of,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{bail, Result};
-use memchr::memmem;
+
+mod build_info;
+pub use self::build_info::*;
+
+mod elf;
 
 mod kip;
 pub use self::kip::*;
 
+mod map;
+pub use self::map::*;
+
 mod metadata;
 pub use self::metadata::*;
 
+mod reader;
+pub use self::reader::*;
+
+mod signature;
+pub use self::signature::*;
+
+#[cfg(test)]
+mod tests;
+
 /// The builder for the final Kernel Image.
+///
+/// The components and settings may be supplied in any order. All
+/// layout decisions, including the version written into both
+/// metadata maps, are deferred until the image is finalized.
 #[derive(Default)]
 pub struct ImageBuilder {
     kernel: Vec<u8>,
@@ -32,184 +52,889 @@ pub struct ImageBuilder {
     loader: Vec<u8>,
     loader_meta: (usize, KernelLoaderMeta),
 
-    kips: Vec<u8>,
-    kip_count: u8,
+    kips: Vec<Vec<u8>>,
+    duplicate_kips: usize,
+    max_ini1_size: Option<usize>,
+    ini1_base: Option<u64>,
+    loader_base: Option<u64>,
+    loader_alignment: Option<usize>,
 
     version: u32,
+    profile: Profile,
+    build_info: Option<BuildInfo>,
 
     page_size: usize,
+    padding_byte: u8,
+    deterministic: bool,
+    strict_kips: bool,
+    load_address: u64,
+    output_mode: Option<u32>,
+    map_file: Option<PathBuf>,
+}
+
+/// Statistics about a Kernel Image produced by
+/// [`ImageBuilder::finalize`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageStats {
+    /// The total size of the image in bytes.
+    pub total_size: usize,
+    /// The number of bytes in the image which are padding.
+    pub padding_bytes: usize,
+}
+
+// The offsets of the components in a Kernel Image, as computed
+// by `ImageBuilder::compute_layout`.
+struct ImageLayout {
+    ini1_header: Option<Ini1Header>,
+    kernel_end: usize,
+    ini1_start: usize,
+    ini1_end: usize,
+    loader_start: usize,
+    loader_end: usize,
+    image_end: usize,
+    total_size: usize,
+}
+
+/// A report about a Kernel Image produced by
+/// [`ImageBuilder::finalize_with_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImageReport {
+    /// Statistics about the written image.
+    pub stats: ImageStats,
+    /// The KIPs in the INI1 record, in order.
+    pub kips: Vec<KipEntry>,
 }
 
 impl ImageBuilder {
     /// Stores the page size that is used by the kernel.
-    pub fn with_page_size(mut self, page_size: usize) -> Self {
+    ///
+    /// This should match the page size of the target's build
+    /// configuration and is used for aligning all components
+    /// in the resulting image. It must be a power of two.
+    pub fn with_page_size(mut self, page_size: usize) -> Result<Self> {
+        if !page_size.is_power_of_two() {
+            bail!("Page size {page_size:#X} is not a power of two");
+        }
+
         self.page_size = page_size;
-        self
+        Ok(self)
     }
 
     /// Loads a raw Kernel binary from the given path and
     /// stores it.
-    pub fn with_kernel<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
-        let mut kernel = fs::read(path)?;
-
-        // We try to find the metadata offset for the kernel first.
-        // However, it must not be at 0 because the image needs to
-        // begin with executable code. At the same time, it is fair
-        // to assume it's a logic bug when metadata are *too* far in.
-        let finder = memmem::Finder::new(KERNEL_MAGIC);
-        let meta_offset = match finder.find(&kernel) {
-            Some(off) if off == 0 || off > 0x10 => {
-                bail!("suspicious metadata offset found; please confirm")
-            }
-            Some(off) => off,
-            None => panic!("Malformed kernel binary!"),
-        };
-
-        // Now deserialize the full kernel meta blob.
-        let meta = KernelMeta::read(&kernel[meta_offset..])?;
-        assert_eq!(meta.magic, u32::from_le_bytes(*KERNEL_MAGIC));
-        assert!(meta.layout.text_start <= meta.layout.text_end);
-        assert!(meta.layout.rodata_start <= meta.layout.rodata_end);
-        assert!(meta.layout.data_start <= meta.layout.data_end);
-        assert!(meta.layout.bss_start <= meta.layout.bss_end);
-        assert!(kernel.len() <= meta.layout.kernel_end as usize);
+    pub fn with_kernel<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        self.with_kernel_bytes(fs::read(path)?)
+    }
 
-        // If the kernel is not the expected length, pad it.
-        let required_padding = meta.layout.kernel_end as usize - kernel.len();
-        if required_padding > 0 {
-            kernel.extend(iter::repeat(0).take(required_padding));
-        }
+    /// Stores a raw Kernel binary from an in-memory buffer.
+    ///
+    /// This applies the same validation as [`ImageBuilder::with_kernel`].
+    pub fn with_kernel_bytes(self, kernel: Vec<u8>) -> Result<Self> {
+        Ok(self.with_kernel_cached(parse_kernel_bytes(kernel)?))
+    }
 
-        // Store the kernel along with its meta.
+    /// Stores a Kernel binary which was already parsed by
+    /// [`parse_kernel`].
+    ///
+    /// This allows building several images from the same Kernel
+    /// without re-reading and re-validating it every time.
+    pub fn with_kernel_cached(mut self, (kernel, meta_offset, meta): ParsedKernel) -> Self {
         self.kernel = kernel;
         self.kernel_meta = (meta_offset, meta);
-
-        Ok(self)
+        self
     }
 
     /// Loads a raw Kernel Loader binary from the given path and
     /// stores it.
-    pub fn with_loader<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
-        let loader = fs::read(path)?;
-
-        // We try to find the metadata offset for the loader first.
-        // However, it must not be at 0 because the image needs to
-        // begin with executable code. At the same time, it is fair
-        // to assume it's a logic bug when metadata are *too* far in.
-        let finder = memmem::Finder::new(KERNEL_LOADER_MAGIC);
-        let meta_offset = match finder.find(&loader) {
-            Some(off) if off == 0 || off > 0x10 => {
-                bail!("suspicious metadata offset found; please confirm")
-            }
-            Some(off) => off,
-            None => panic!("Malformed kernel binary!"),
-        };
+    pub fn with_loader<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        self.with_loader_bytes(fs::read(path)?)
+    }
 
-        // Now deserialize the full kernel loader meta blob.
-        let meta = KernelLoaderMeta::read(&loader[meta_offset..])?;
-        assert_eq!(meta.magic, u32::from_le_bytes(*KERNEL_LOADER_MAGIC));
-        assert_eq!(meta.marker, 0xCCCCCCCC);
+    /// Stores a raw Kernel Loader binary from an in-memory buffer.
+    ///
+    /// This applies the same validation as [`ImageBuilder::with_loader`].
+    pub fn with_loader_bytes(self, loader: Vec<u8>) -> Result<Self> {
+        Ok(self.with_loader_cached(parse_loader_bytes(loader)?))
+    }
 
+    /// Stores a Kernel Loader binary which was already parsed by
+    /// [`parse_loader`].
+    ///
+    /// This allows building several images from the same loader
+    /// without re-reading and re-validating it every time.
+    pub fn with_loader_cached(mut self, (loader, meta_offset, meta): ParsedLoader) -> Self {
         self.loader = loader;
         self.loader_meta = (meta_offset, meta);
-
-        Ok(self)
+        self
     }
 
     /// Loads a Kernel Initial Process binary from the given path
     /// and stores it.
     ///
     /// KIPs are expected to start with [`KIP_MAGIC`] and the total
-    /// number of allowed KIPs is [`MAX_KIP_COUNT`].
-    pub fn add_kip<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
-        if self.kip_count > MAX_KIP_COUNT {
-            bail!("Number of allowed KIPs exceeded");
+    /// number of allowed KIPs is [`MAX_KIP_COUNT`]. A KIP with the
+    /// same name as a previously added one replaces it, see
+    /// [`ImageBuilder::with_strict_kips`].
+    pub fn add_kip<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        self.add_kip_bytes(fs::read(path)?)
+    }
+
+    /// Loads all Kernel Initial Process binaries from the given
+    /// directory and stores them.
+    ///
+    /// KIPs are added in order of their file names, so the result
+    /// is deterministic. Files which do not start with [`KIP_MAGIC`]
+    /// are skipped with a warning, but exceeding [`MAX_KIP_COUNT`]
+    /// is still an error.
+    pub fn add_kips_from_dir<P: AsRef<Path>>(mut self, dir: P) -> Result<Self> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+
+        for path in paths.into_iter().filter(|p| p.is_file()) {
+            let kip = fs::read(&path)?;
+            if !kip.starts_with(KIP_MAGIC) {
+                eprintln!("warning: skipping {}: not a KIP binary", path.display());
+                continue;
+            }
+
+            self = self.add_kip_bytes(kip)?;
         }
 
-        let kip = fs::read(path)?;
+        Ok(self)
+    }
+
+    /// Stores a Kernel Initial Process binary from an in-memory
+    /// buffer.
+    ///
+    /// This applies the same validation as [`ImageBuilder::add_kip`].
+    pub fn add_kip_bytes(mut self, kip: Vec<u8>) -> Result<Self> {
+        // Anything shorter than a header cannot be a valid KIP, and
+        // must not be sliced into below.
+        if kip.len() < KIP_HEADER_SIZE {
+            bail!("KIP binary too small: {} bytes", kip.len());
+        }
         if &kip[..KIP_MAGIC.len()] != KIP_MAGIC {
             bail!("Invalid KIP binary supplied: no header magic found");
         }
 
-        self.kips.extend(kip);
-        self.kip_count += 1;
+        // Cross-check the declared segment sizes with the actual size.
+        match kip_size(&kip) {
+            Some(size) if size == kip.len() => {}
+            declared => {
+                let declared = declared.unwrap_or(KIP_HEADER_SIZE);
+                let msg = format!(
+                    "KIP {} declares {declared:#X} bytes, but is {:#X} bytes in size",
+                    self.kips.len(),
+                    kip.len()
+                );
+                if self.strict_kips {
+                    bail!("{msg}");
+                }
+                eprintln!("warning: {msg}");
+            }
+        }
+
+        // Look for a KIP with the same name which was added before.
+        let name = kip_name(&kip).filter(|name| !name.is_empty());
+        let duplicate = self
+            .kips
+            .iter()
+            .position(|k| name.is_some() && kip_name(k) == name);
+        if let Some(index) = duplicate {
+            let msg = format!(
+                "KIP {} has the same name {:?} as KIP {index}",
+                self.kips.len(),
+                name.unwrap_or_default()
+            );
+            if self.strict_kips {
+                bail!("{msg}");
+            }
+            eprintln!("warning: {msg}; replacing it");
+        } else if self.kips.len() >= MAX_KIP_COUNT as usize {
+            bail!("Number of allowed KIPs exceeded");
+        }
+
+        // Without an explicit budget, the check is deferred to the
+        // final build so that the budget can still be raised later.
+        if self.max_ini1_size.is_some() {
+            let replaced = duplicate.map(|i| self.kips[i].len()).unwrap_or(0);
+            self.check_ini1_size(self.kip_bytes() - replaced + kip.len())?;
+        }
+
+        match duplicate {
+            Some(index) => {
+                self.kips[index] = kip;
+                self.duplicate_kips += 1;
+            }
+            None => self.kips.push(kip),
+        }
 
         Ok(self)
     }
 
+    /// Turns KIPs whose size does not match the segment sizes in
+    /// their header into an error.
+    ///
+    /// By default, [`ImageBuilder::add_kip`] only prints a warning
+    /// for them, although this usually indicates a truncated or
+    /// otherwise mis-built KIP binary. KIPs which were added before
+    /// will fail the final build instead.
+    ///
+    /// The same applies to KIPs with the same name as a previously
+    /// added one, which would otherwise replace it in place.
+    pub fn with_strict_kips(mut self) -> Self {
+        self.strict_kips = true;
+        self
+    }
+
+    /// Sets the maximum size of the INI1 record in bytes, including
+    /// its header.
+    ///
+    /// Once set, adding KIPs beyond this budget fails immediately.
+    /// Otherwise, the default budget of [`MAX_INI1_SIZE`] is only
+    /// enforced when the image is finalized.
+    pub fn with_max_ini1_size(mut self, size: usize) -> Self {
+        self.max_ini1_size = Some(size);
+        self
+    }
+
+    // Gets the total size of all stored KIPs in bytes.
+    fn kip_bytes(&self) -> usize {
+        self.kips.iter().map(Vec::len).sum()
+    }
+
+    // Checks that `kip_bytes` bytes of KIPs fit into the INI1 budget.
+    fn check_ini1_size(&self, kip_bytes: usize) -> Result<()> {
+        let limit = self.max_ini1_size.unwrap_or(MAX_INI1_SIZE);
+        let size = size_of::<Ini1Header>() + kip_bytes;
+        if size > limit {
+            bail!(
+                "INI1 record of {size:#X} bytes exceeds its budget of {limit:#X} bytes by {:#X}",
+                size - limit
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Overrides the offset of the INI1 record in the image.
+    ///
+    /// The offset must be page-aligned and must not overlap the
+    /// Kernel. When [`None`], which is the default, the record is
+    /// placed at the first page boundary after the Kernel.
+    pub fn with_ini1_base(mut self, base: Option<u64>) -> Self {
+        self.ini1_base = base;
+        self
+    }
+
+    /// Overrides the offset of the Kernel Loader in the image.
+    ///
+    /// The offset must be page-aligned, or aligned as given by
+    /// [`ImageBuilder::with_loader_alignment`], and must come after
+    /// the INI1 record. When [`None`], which is the default, the
+    /// loader is placed at the first suitably aligned offset after
+    /// the INI1 record.
+    pub fn with_loader_base(mut self, base: Option<u64>) -> Self {
+        self.loader_base = base;
+        self
+    }
+
+    /// Aligns the offset of the Kernel Loader in the image to
+    /// `align` bytes, which must be a power of two.
+    ///
+    /// This is for bootloaders that expect the loader at a coarser
+    /// boundary than the page size, e.g. `0x10000`. The gap before
+    /// the loader is filled with padding. An explicit loader base
+    /// must be aligned accordingly.
+    ///
+    /// `align` is checked to be at least the page size when the
+    /// image is finalized.
+    pub fn with_loader_alignment(mut self, align: usize) -> Result<Self> {
+        if !align.is_power_of_two() {
+            bail!("Loader alignment {align:#X} is not a power of two");
+        }
+
+        self.loader_alignment = Some(align);
+        Ok(self)
+    }
+
     /// Sets the version for the Kernel Image.
     pub fn with_version(mut self, major: u8, minor: u8, micro: u8) -> Self {
         self.version = ((major as u32) << 24) | ((minor as u32) << 16) | ((micro as u32) << 8);
         self
     }
 
+    /// Records the profile the Kernel Image was built with.
+    ///
+    /// It is stored in the low byte of the version in both the
+    /// Kernel and the Kernel Loader metadata, see [`KernelMeta::version`].
+    /// The default is [`Profile::Release`].
+    pub fn with_build_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Embeds the given [`BuildInfo`] into the Kernel Image.
+    ///
+    /// It will be placed right after the Kernel Loader and can
+    /// be recovered from the final image with [`read_build_info`].
+    pub fn with_build_info(mut self, info: BuildInfo) -> Self {
+        self.build_info = Some(info);
+        self
+    }
+
+    /// Sets the byte value to fill all padding in the image with.
+    ///
+    /// This covers the gaps between and after all components, as
+    /// well as the tail of the Kernel up to its `kernel_end`. The
+    /// default is `0x00`; `0xFF` matches erased flash memory.
+    pub fn with_padding_byte(mut self, byte: u8) -> Self {
+        self.padding_byte = byte;
+        self
+    }
+
+    /// Makes the build reproducible.
+    ///
+    /// Building the same inputs twice will then produce identical
+    /// images. The only affected bytes are the `timestamp` of the
+    /// embedded [`BuildInfo`], which will be zeroed. All padding
+    /// is written with the configured padding byte.
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Sets the physical address the Kernel Image will be loaded to.
+    ///
+    /// This is only used by [`ImageBuilder::finalize_elf`] and
+    /// defaults to `0`. The address must be aligned to the page
+    /// size, which is checked when the builder is validated.
+    pub fn with_load_address(mut self, address: u64) -> Self {
+        self.load_address = address;
+        self
+    }
+
+    /// Sets the Unix permission bits of the output file, e.g. `0o644`.
+    ///
+    /// When [`None`], which is the default, the permissions of an
+    /// existing file are kept and new files are created with the
+    /// default permissions of the OS. This has no effect on other
+    /// platforms than Unix.
+    pub fn with_output_mode(mut self, mode: Option<u32>) -> Self {
+        self.output_mode = mode;
+        self
+    }
+
+    /// Writes an [`ImageMap`] of the finalized image as a table
+    /// to `path`.
+    ///
+    /// The map lists the offsets of the Kernel, the INI1 header,
+    /// every KIP by name, the Kernel Loader, the build info and all
+    /// padding in between. This allows attributing an offset into
+    /// the image, e.g. from a crash dump, to the component it
+    /// belongs to.
+    pub fn with_map_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.map_file = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Checks that the builder's state allows finalizing the image,
+    /// without writing anything.
+    ///
+    /// This runs all the checks the `finalize` methods perform:
+    /// Kernel and Kernel Loader must be present, the INI1 record
+    /// must fit into its budget and all components must be placed
+    /// without overlap at properly aligned offsets, and the load
+    /// address must be page-aligned. With
+    /// [`ImageBuilder::with_strict_kips`], mismatched and duplicate
+    /// KIPs are rejected as well.
+    pub fn validate(&self) -> Result<()> {
+        self.compute_layout().map(drop)
+    }
+
     /// Finalizes the build and writes the resulting Kernel Image
     /// to `outfile`.
-    pub fn finalize<P: AsRef<Path>>(mut self, outfile: P) -> Result<()> {
+    ///
+    /// The builder is [validated][ImageBuilder::validate] before the
+    /// output file is touched.
+    ///
+    /// On success, statistics about the written image are returned.
+    pub fn finalize<P: AsRef<Path>>(self, outfile: P) -> Result<ImageStats> {
+        self.validate()?;
+
+        let mut output = create_output(outfile, self.output_mode)?;
+        self.write_image(&mut output).map(|(stats, _)| stats)
+    }
+
+    /// Finalizes the build like [`ImageBuilder::finalize`] and
+    /// additionally writes a listing of all KIPs to `report_out`.
+    ///
+    /// Every line of the listing describes one KIP by its name,
+    /// size, flags and offset within the INI1 record, separated
+    /// by tabs.
+    pub fn finalize_with_report<P: AsRef<Path>, R: AsRef<Path>>(
+        self,
+        outfile: P,
+        report_out: R,
+    ) -> Result<ImageReport> {
+        let kips = self.kip_entries()?;
+        let stats = self.finalize(outfile)?;
+
+        let mut report = io::BufWriter::new(fs::File::create(report_out)?);
+        for kip in &kips {
+            writeln!(
+                report,
+                "{}\t{:#X}\t{:#04X}\t{:#X}",
+                kip.name, kip.size, kip.flags, kip.offset
+            )?;
+        }
+        report.flush()?;
+
+        Ok(ImageReport { stats, kips })
+    }
+
+    // Parses the headers of all stored KIPs.
+    fn kip_entries(&self) -> Result<Vec<KipEntry>> {
+        let mut entries = Vec::with_capacity(self.kips.len());
+
+        let mut offset = size_of::<Ini1Header>();
+        for (i, kip) in self.kips.iter().enumerate() {
+            match KipEntry::parse(kip, offset) {
+                Some(entry) => entries.push(entry),
+                None => bail!("KIP {i} is malformed"),
+            }
+
+            offset += kip.len();
+        }
+
+        Ok(entries)
+    }
+
+    /// Finalizes the build and writes the resulting Kernel Image
+    /// wrapped in an ELF file to `outfile`.
+    ///
+    /// The image is placed in a single loadable segment at the
+    /// address configured with [`ImageBuilder::with_load_address`].
+    /// The `kernel_base`, `ini1_base` and `loader_base` symbols
+    /// mark the component boundaries for use in debuggers.
+    ///
+    /// On success, statistics about the wrapped image are returned.
+    pub fn finalize_elf<P: AsRef<Path>>(self, outfile: P) -> Result<ImageStats> {
+        let (load_address, page_size) = (self.load_address, self.page_size);
+        let output_mode = self.output_mode;
+
+        let mut image = io::Cursor::new(Vec::new());
+        let (stats, meta) = self.write_image(&mut image)?;
+
+        let symbols = [
+            ("kernel_base", 0),
+            ("ini1_base", meta.ini1_base as usize),
+            ("loader_base", meta.loader_base as usize),
+        ];
+        let mut output = create_output(outfile, output_mode)?;
+        elf::write_elf(
+            &mut output,
+            image.get_ref(),
+            load_address,
+            page_size,
+            &symbols,
+        )?;
+
+        Ok(stats)
+    }
+
+    /// Finalizes the build and writes the resulting Kernel Image
+    /// to `outfile`, followed by a detached signature.
+    ///
+    /// `sign_fn` receives the complete image and returns the
+    /// signature over it, which is appended to the image along
+    /// with a trailer of its length and [`SIGNATURE_MAGIC`]. This
+    /// crate does not implement any cryptography itself; use
+    /// [`read_signature`] to split a signed image for verification.
+    ///
+    /// On success, statistics about the image without the signature
+    /// are returned.
+    pub fn finalize_signed<P, F>(self, outfile: P, sign_fn: F) -> Result<ImageStats>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&[u8]) -> Vec<u8>,
+    {
+        let output_mode = self.output_mode;
+
+        let mut image = io::Cursor::new(Vec::new());
+        let (stats, _) = self.write_image(&mut image)?;
+        let image = image.into_inner();
+
+        let signature = sign_fn(&image);
+        if signature.len() > u32::MAX as usize {
+            bail!("Signature of {:#X} bytes is too large", signature.len());
+        }
+
+        let mut output = create_output(outfile, output_mode)?;
+        output.write_all(&image)?;
+        write_signature(&mut output, &signature)?;
+
+        Ok(stats)
+    }
+
+    // Runs all checks on the builder's state and computes the
+    // offsets of the components in the final image.
+    fn compute_layout(&self) -> Result<ImageLayout> {
         if self.kernel_meta.0 == 0 || self.loader.is_empty() {
             bail!("Cannot build Kernel Image without at least Kernel and Loader");
         }
+        if self.page_size == 0 {
+            bail!("Cannot build Kernel Image without a page size");
+        }
+        if self.load_address % self.page_size as u64 != 0 {
+            bail!(
+                "Load address {:#X} is not aligned to the page size {:#X}",
+                self.load_address,
+                self.page_size
+            );
+        }
+        let loader_alignment = self.loader_alignment.unwrap_or(self.page_size);
+        if loader_alignment < self.page_size {
+            bail!(
+                "Loader alignment {loader_alignment:#X} is smaller than the page size {:#X}",
+                self.page_size
+            );
+        }
+        let kip_bytes = self.kip_bytes();
+        if !self.kips.is_empty() {
+            self.check_ini1_size(kip_bytes)?;
+        }
+        if self.strict_kips {
+            let mismatched = self
+                .kips
+                .iter()
+                .filter(|k| kip_size(k) != Some(k.len()))
+                .count();
+            if mismatched != 0 {
+                bail!("Number of KIPs not matching their declared size: {mismatched}");
+            }
+            if self.duplicate_kips != 0 {
+                bail!(
+                    "Number of KIPs replaced by duplicates: {}",
+                    self.duplicate_kips
+                );
+            }
+        }
 
         // Build the INI1 header if necessary and determine its length.
-        let ini1_header = build_ini1_header(self.kips.len(), self.kip_count);
-        let ini1_header_len = ini1_header.as_ref().map(|h| h.len()).unwrap_or(0);
+        let ini1_header = build_ini1_header(kip_bytes, self.kips.len() as u8);
+        let ini1_header_len = ini1_header.as_ref().map(|h| h.size()).unwrap_or(0);
 
         // Calculate the start and end offsets of the INI1 segment.
-        let ini1_start = align_up(self.kernel_meta.1.layout.kernel_end as usize, self.page_size);
-        let ini1_end = ini1_start + ini1_header_len + self.kips.len();
+        let kernel_end = self.kernel_meta.1.layout.kernel_end as usize;
+        let ini1_start = match self.ini1_base {
+            Some(base) => check_base("INI1", base, kernel_end, self.page_size)?,
+            None => align_up(kernel_end, self.page_size),
+        };
+        let ini1_end = ini1_start + ini1_header_len + kip_bytes;
 
         // Calculate the start and end offsets of the Kernel Loader.
-        let loader_start =
-            align_up(ini1_end, self.page_size) + if ini1_header_len == 0 { self.page_size } else { 0 };
+        // Without an INI1 record, the loader must not be placed at
+        // `ini1_base` either; see `place_loader` for details.
+        let loader_start = match self.loader_base {
+            Some(base) if ini1_header_len != 0 => {
+                check_base("Kernel Loader", base, ini1_end, loader_alignment)?
+            }
+            Some(base) => check_base("Kernel Loader", base, ini1_end + 1, loader_alignment)?,
+            None => align_up(
+                place_loader(ini1_end, ini1_header_len != 0, self.page_size),
+                loader_alignment,
+            ),
+        };
         let loader_end = loader_start + self.loader.len();
 
+        // Calculate the end of the image, including build info and
+        // a trailing page of padding at an aligned offset.
+        let image_end = loader_end + self.build_info.as_ref().map(|i| i.size()).unwrap_or(0);
+        let total_size = align_up(image_end, self.page_size) + self.page_size;
+
+        Ok(ImageLayout {
+            ini1_header,
+            kernel_end,
+            ini1_start,
+            ini1_end,
+            loader_start,
+            loader_end,
+            image_end,
+            total_size,
+        })
+    }
+
+    // Writes the final Kernel Image to `output` and returns its
+    // statistics along with the final Kernel metadata.
+    fn write_image<W: Write + Seek>(mut self, output: &mut W) -> Result<(ImageStats, KernelMeta)> {
+        let ImageLayout {
+            ini1_header,
+            kernel_end,
+            ini1_start,
+            ini1_end,
+            loader_start,
+            loader_end,
+            image_end,
+            total_size,
+        } = self.compute_layout()?;
+        let ini1_header_len = ini1_header.as_ref().map(|h| h.size()).unwrap_or(0);
+
         // Update our headers accordingly.
         self.kernel_meta.1.ini1_base = ini1_start as u64;
         self.kernel_meta.1.loader_base = loader_start as u64;
-        self.kernel_meta.1.version = self.version;
-        self.loader_meta.1.version = self.version;
+        let version = self.version | self.profile.version_flags();
+        self.kernel_meta.1.version = version;
+        self.loader_meta.1.version = version;
+        self.loader_meta.1.size = self.loader.len() as u32;
+
+        // Strip anything that varies between builds, if requested.
+        if let (true, Some(info)) = (self.deterministic, &mut self.build_info) {
+            info.timestamp = 0;
+        }
 
         // Now build the resulting output binary.
-        let mut output = fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(outfile)?;
+        let mut padding_bytes = 0;
         {
             // Write the initial bits of kernel code.
             output.write_all(&self.kernel[..self.kernel_meta.0])?;
 
             // Re-serialize the kernel metadata.
-            self.kernel_meta.1.write(&mut output)?;
+            self.kernel_meta.1.write(output)?;
 
             // Write the remaining bits of kernel code.
             output.write_all(&self.kernel[(self.kernel_meta.0 + self.kernel_meta.1.size())..])?;
 
             // Write the INI1 record of Kernel Initial Processes.
-            output.seek(SeekFrom::Start(ini1_start as u64))?;
-            output.write_all(&ini1_header.unwrap_or_default())?;
-            output.write_all(&self.kips)?;
+            padding_bytes += pad_to(output, ini1_start, self.padding_byte)?;
+            if let Some(header) = &ini1_header {
+                header.write(output)?;
+            }
+            for kip in &self.kips {
+                output.write_all(kip)?;
+            }
 
             // Write the initial bits of loader code.
-            output.seek(SeekFrom::Start(loader_start as u64))?;
+            padding_bytes += pad_to(output, loader_start, self.padding_byte)?;
             output.write_all(&self.loader[..self.loader_meta.0])?;
 
             // Re-serialize the loader metadata.
-            self.loader_meta.1.write(&mut output)?;
+            self.loader_meta.1.write(output)?;
 
             // Write the remaining bits of loader code.
             output.write_all(&self.loader[(self.loader_meta.0 + self.loader_meta.1.size())..])?;
 
-            // Append trailing padding at an aligned image end.
-            output.seek(SeekFrom::Start(align_up(loader_end, self.page_size) as u64))?;
-            output.write_all(&vec![0; self.page_size])?;
+            // Write the build info, if we have any.
+            if let Some(info) = &self.build_info {
+                info.write(output)?;
+            }
+
+            // Append trailing padding up to the end of the image.
+            padding_bytes += pad_to(output, total_size, self.padding_byte)?;
         }
 
-        Ok(())
+        // Write the map of all regions in the image, if requested.
+        if let Some(path) = &self.map_file {
+            let mut map = ImageMap::default();
+            map.push("kernel", 0..kernel_end);
+            map.push("padding", kernel_end..ini1_start);
+            map.push("ini1_header", ini1_start..ini1_start + ini1_header_len);
+
+            let mut offset = ini1_start + ini1_header_len;
+            for (i, kip) in self.kips.iter().enumerate() {
+                let name = kip_name(kip)
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| i.to_string());
+                map.push(format!("kip:{name}"), offset..offset + kip.len());
+                offset += kip.len();
+            }
+
+            map.push("padding", ini1_end..loader_start);
+            map.push("loader", loader_start..loader_end);
+            map.push("build_info", loader_end..image_end);
+            map.push("padding", image_end..total_size);
+
+            fs::write(path, map.to_string())?;
+        }
+
+        let stats = ImageStats {
+            total_size,
+            padding_bytes,
+        };
+        Ok((stats, self.kernel_meta.1))
+    }
+}
+
+/// A Kernel binary along with the offset and contents of its
+/// metadata, as returned by [`parse_kernel`].
+pub type ParsedKernel = (Vec<u8>, usize, KernelMeta);
+
+/// A Kernel Loader binary along with the offset and contents of
+/// its metadata, as returned by [`parse_loader`].
+pub type ParsedLoader = (Vec<u8>, usize, KernelLoaderMeta);
+
+/// Loads and validates a raw Kernel binary from the given path.
+///
+/// The result can be stored in any number of [`ImageBuilder`]s
+/// with [`ImageBuilder::with_kernel_cached`].
+pub fn parse_kernel<P: AsRef<Path>>(path: P) -> Result<ParsedKernel> {
+    parse_kernel_bytes(fs::read(path)?)
+}
+
+/// Loads and validates a raw Kernel Loader binary from the given
+/// path.
+///
+/// The result can be stored in any number of [`ImageBuilder`]s
+/// with [`ImageBuilder::with_loader_cached`].
+pub fn parse_loader<P: AsRef<Path>>(path: P) -> Result<ParsedLoader> {
+    parse_loader_bytes(fs::read(path)?)
+}
+
+fn parse_kernel_bytes(kernel: Vec<u8>) -> Result<ParsedKernel> {
+    // We try to find the metadata offset for the kernel first.
+    let meta_offset = find_meta_offset(&kernel, KERNEL_MAGIC)?;
+
+    // Now deserialize the full kernel meta blob.
+    let meta = KernelMeta::read(&kernel[meta_offset..])?;
+    assert_eq!(meta.magic, u32::from_le_bytes(*KERNEL_MAGIC));
+    assert!(meta.layout.text_start <= meta.layout.text_end);
+    assert!(meta.layout.rodata_start <= meta.layout.rodata_end);
+    assert!(meta.layout.data_start <= meta.layout.data_end);
+    assert!(meta.layout.bss_start <= meta.layout.bss_end);
+    assert!(kernel.len() <= meta.layout.kernel_end as usize);
+
+    Ok((kernel, meta_offset, meta))
+}
+
+fn parse_loader_bytes(loader: Vec<u8>) -> Result<ParsedLoader> {
+    // We try to find the metadata offset for the loader first.
+    let meta_offset = find_meta_offset(&loader, KERNEL_LOADER_MAGIC)?;
+
+    // Now deserialize the full kernel loader meta blob.
+    let meta = KernelLoaderMeta::read(&loader[meta_offset..])?;
+    assert_eq!(meta.magic, u32::from_le_bytes(*KERNEL_LOADER_MAGIC));
+    if meta.marker != 0xCCCCCCCC {
+        bail!(
+            "Loader metadata marker mismatch: expected 0xCCCCCCCC, got {:#X}; \
+             loader binary may be stale",
+            meta.marker
+        );
+    }
+
+    // The version is only filled in when finalizing the image.
+    if meta.version != 0 {
+        eprintln!(
+            "warning: loader metadata already has version {:#X}; it will be overwritten",
+            meta.version
+        );
+    }
+
+    Ok((loader, meta_offset, meta))
+}
+
+/// Reads the [`BuildInfo`] embedded in a Kernel Image at `path`.
+///
+/// The image is validated like [`ImageReader::open`] does, since
+/// the build info is located through the loader metadata. Returns
+/// [`None`] when the image was built without it.
+pub fn read_build_info<P: AsRef<Path>>(path: P) -> Result<Option<BuildInfo>> {
+    Ok(ImageReader::open(path)?.build_info().cloned())
+}
+
+/// Reads the `(major, minor, micro)` version of the Kernel Image
+/// at `path`, as set by [`ImageBuilder::with_version`].
+///
+/// Only the Kernel metadata at the start of the image are read,
+/// so this is cheap even for large images.
+pub fn read_version<P: AsRef<Path>>(path: P) -> Result<(u8, u8, u8)> {
+    // The metadata are located in the first 0x10 bytes of the Kernel,
+    // which in turn is placed at the very start of the image.
+    let mut header = Vec::new();
+    fs::File::open(path)?
+        .take((0x10 + KernelMeta::default().size()) as u64)
+        .read_to_end(&mut header)?;
+
+    let meta_offset = find_meta_offset(&header, KERNEL_MAGIC)?;
+    let meta = KernelMeta::read(&header[meta_offset..])?;
+    Ok(unpack_version(meta.version))
+}
+
+// Unpacks a version value as packed by `ImageBuilder::with_version`.
+pub(crate) const fn unpack_version(version: u32) -> (u8, u8, u8) {
+    (
+        (version >> 24) as u8,
+        (version >> 16) as u8,
+        (version >> 8) as u8,
+    )
+}
+
+// Creates the output file at `path`, truncating any existing file,
+// and applies the permissions given by `mode` on Unix.
+fn create_output<P: AsRef<Path>>(path: P, mode: Option<u32>) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        // The mode only applies to newly created files, so
+        // existing ones need to be updated separately.
+        let file = options.mode(mode).open(path)?;
+        file.set_permissions(fs::Permissions::from_mode(mode))?;
+        return Ok(file);
+    }
+
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    options.open(path)
+}
+
+// Pads `output` with `byte` up to `offset` and returns the number
+// of padding bytes which were written.
+fn pad_to<W: Write + Seek>(output: &mut W, offset: usize, byte: u8) -> io::Result<usize> {
+    let padding = offset - output.stream_position()? as usize;
+    io::copy(&mut io::repeat(byte).take(padding as u64), output)?;
+    Ok(padding)
+}
+
+// Validates a user-supplied offset `base` for a component in the
+// image which must be aligned to `align` and must not start
+// before `min`.
+fn check_base(name: &str, base: u64, min: usize, align: usize) -> Result<usize> {
+    let base = base as usize;
+    if base & (align - 1) != 0 {
+        bail!("{name} base {base:#X} is not aligned to {align:#X}");
+    }
+    if base < min {
+        bail!("{name} base {base:#X} overlaps the preceding component");
+    }
+
+    Ok(base)
+}
+
+/// Determines the offset of the Kernel Loader in the image.
+///
+/// The loader is placed at the first page boundary after the INI1
+/// record. When there are no KIPs, the INI1 record is empty and
+/// would share its offset with the loader. In that case, an extra
+/// page of padding is reserved so that `ini1_base` points to memory
+/// without a valid INI1 magic rather than to the loader's code.
+#[inline]
+const fn place_loader(ini1_end: usize, has_ini1: bool, page_size: usize) -> usize {
+    let loader_start = align_up(ini1_end, page_size);
+    if has_ini1 {
+        loader_start
+    } else {
+        loader_start + page_size
     }
 }
 
 #[inline]
-const fn align_up(value: usize, align: usize) -> usize {
+pub(crate) const fn align_up(value: usize, align: usize) -> usize {
     assert!(align.is_power_of_two());
     (value + align - 1) & !(align - 1)
 }
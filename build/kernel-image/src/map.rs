@@ -0,0 +1,69 @@
+use std::{fmt, ops::Range};
+
+/// A named region of a Kernel Image, given by its offsets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapRegion {
+    /// The name of the region, e.g. `kernel` or `kip:FS`.
+    pub name: String,
+    /// The start offset of the region, inclusive.
+    pub start: usize,
+    /// The end offset of the region, exclusive.
+    pub end: usize,
+}
+
+impl MapRegion {
+    /// Gets the size of the region in bytes.
+    pub fn size(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// A map of all regions in a Kernel Image, as produced by
+/// [`ImageBuilder::with_map_file`][crate::ImageBuilder::with_map_file].
+///
+/// The regions are sorted by offset and cover the whole image
+/// without gaps. Padding between components is listed as regions
+/// named `padding`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImageMap {
+    /// The regions of the image, in order.
+    pub regions: Vec<MapRegion>,
+}
+
+impl ImageMap {
+    /// Finds the region which contains the given image offset.
+    pub fn region_at(&self, offset: usize) -> Option<&MapRegion> {
+        self.regions
+            .iter()
+            .find(|r| (r.start..r.end).contains(&offset))
+    }
+
+    // Appends a region, unless it is empty.
+    pub(crate) fn push(&mut self, name: impl Into<String>, range: Range<usize>) {
+        if !range.is_empty() {
+            self.regions.push(MapRegion {
+                name: name.into(),
+                start: range.start,
+                end: range.end,
+            });
+        }
+    }
+}
+
+impl fmt::Display for ImageMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<12}{:<12}{:<12}Region", "Start", "End", "Size")?;
+        for region in &self.regions {
+            writeln!(
+                f,
+                "{:<#12X}{:<#12X}{:<#12X}{}",
+                region.start,
+                region.end,
+                region.size(),
+                region.name
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use std::{io, mem::size_of};
 
+use anyhow::{bail, Result};
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use memchr::memmem;
 
 /// The magic value of the Kernel Loader.
 pub const KERNEL_LOADER_MAGIC: &[u8; 4] = b"SLD0";
@@ -8,6 +10,59 @@ pub const KERNEL_LOADER_MAGIC: &[u8; 4] = b"SLD0";
 /// The magic value of the Kernel.
 pub const KERNEL_MAGIC: &[u8; 4] = b"SKN0";
 
+/// The flag in the low byte of [`KernelMeta::version`] which marks
+/// images built with the [`Profile::Debug`] profile.
+pub const VERSION_FLAG_DEBUG: u32 = 1 << 0;
+
+/// The profile a Kernel Image was built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// An optimized release build.
+    Release,
+    /// An unoptimized build with debug assertions.
+    Debug,
+}
+
+impl Profile {
+    /// Extracts the profile from the flags of a packed version.
+    pub const fn from_version(version: u32) -> Self {
+        if version & VERSION_FLAG_DEBUG != 0 {
+            Self::Debug
+        } else {
+            Self::Release
+        }
+    }
+
+    /// Gets the version flags which encode the profile.
+    pub const fn version_flags(self) -> u32 {
+        match self {
+            Self::Release => 0,
+            Self::Debug => VERSION_FLAG_DEBUG,
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::Release
+    }
+}
+
+/// Finds the offset of the metadata identified by `magic`
+/// in a Kernel or Kernel Loader binary.
+pub(crate) fn find_meta_offset(binary: &[u8], magic: &[u8]) -> Result<usize> {
+    // The metadata must not be at 0 because the binary needs to
+    // begin with executable code. At the same time, it is fair
+    // to assume it's a logic bug when metadata are *too* far in.
+    match memmem::find(binary, magic) {
+        Some(off) if off == 0 || off > 0x10 => {
+            bail!("suspicious metadata offset found; please confirm")
+        }
+        Some(off) => Ok(off),
+        None => bail!("Malformed binary: no metadata found"),
+    }
+}
+
 /// Representation of the Kernel metadata map.
 ///
 /// This must be kept in sync with actual kernel code
@@ -24,6 +79,10 @@ pub struct KernelMeta {
     /// The base address of the Kernel Loader blob.
     pub loader_base: u64,
     /// The current kernel version.
+    ///
+    /// The major, minor and micro version are stored in the upper
+    /// three bytes, from most to least significant. The low byte
+    /// holds flags, such as [`VERSION_FLAG_DEBUG`].
     pub version: u32,
     /// The current layout of the kernel binary.
     pub layout: KernelLayout,
@@ -61,10 +120,14 @@ impl KernelMeta {
 }
 
 /// Representation of the kernel's binary section layout.
+///
+/// This mirrors `libkern::layout::KernelLayout`, which the Kernel
+/// and the Kernel Loader use, field by field. Both definitions
+/// assert the same offset for every field, so keep them in sync.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[repr(C)]
 pub struct KernelLayout {
-    /// End of the kernel .text section.
+    /// Start of the kernel .text section.
     pub text_start: u32,
     /// End of the kernel .text section.
     pub text_end: u32,
@@ -139,6 +202,11 @@ pub struct KernelLoaderMeta {
     pub version: u32,
     /// A currently unused marker value.
     pub marker: u32,
+    /// The size of the Kernel Loader binary in the image.
+    ///
+    /// This is only filled in when the image is built, and used to
+    /// locate the [`BuildInfo`][crate::BuildInfo] following the loader.
+    pub size: u32,
 }
 
 impl KernelLoaderMeta {
@@ -148,13 +216,14 @@ impl KernelLoaderMeta {
             magic: data.read_u32::<LE>()?,
             version: data.read_u32::<LE>()?,
             marker: data.read_u32::<LE>()?,
+            size: data.read_u32::<LE>()?,
         })
     }
 
     /// Gets the binary size of the meta object.
     pub fn size(&self) -> usize {
-        let size = size_of::<u32>() * 3;
-        assert_eq!(size, 0xC);
+        let size = size_of::<u32>() * 4;
+        assert_eq!(size, 0x10);
         size
     }
 
@@ -163,6 +232,7 @@ impl KernelLoaderMeta {
         writer.write_u32::<LE>(self.magic)?;
         writer.write_u32::<LE>(self.version)?;
         writer.write_u32::<LE>(self.marker)?;
+        writer.write_u32::<LE>(self.size)?;
 
         Ok(())
     }
@@ -0,0 +1,218 @@
+use std::{fs, ops::Range, path::Path};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    find_meta_offset, kip_size, read_build_info_at, unpack_version, BuildInfo, Ini1Header,
+    KernelLoaderMeta, KernelMeta, Profile, INI1_MAGIC, KERNEL_LOADER_MAGIC, KERNEL_MAGIC,
+    MAX_KIP_COUNT,
+};
+
+/// A reader for finalized Kernel Images.
+///
+/// This is the counterpart to [`ImageBuilder`][crate::ImageBuilder]
+/// which takes apart an image into the components it was stitched
+/// together from. Constructing a reader validates that the image
+/// is structurally sound.
+pub struct ImageReader {
+    image: Vec<u8>,
+    kernel_meta: (usize, KernelMeta),
+    loader_meta: (usize, KernelLoaderMeta),
+
+    ini1_size: usize,
+    kips: Vec<Range<usize>>,
+
+    loader_end: usize,
+    build_info: Option<BuildInfo>,
+}
+
+impl ImageReader {
+    /// Reads and parses the Kernel Image at the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(fs::read(path)?)
+    }
+
+    /// Parses a Kernel Image from an in-memory buffer.
+    pub fn new(image: Vec<u8>) -> Result<Self> {
+        // Locate the kernel metadata and validate the layout.
+        let kernel_meta_offset = find_meta_offset(&image, KERNEL_MAGIC)?;
+        let kernel_meta = KernelMeta::read(&image[kernel_meta_offset..])?;
+        let layout = &kernel_meta.layout;
+        if layout.text_start > layout.text_end
+            || layout.rodata_start > layout.rodata_end
+            || layout.data_start > layout.data_end
+            || layout.bss_start > layout.bss_end
+        {
+            bail!("Kernel section layout is inconsistent");
+        }
+
+        // Validate the placement of the other components.
+        let kernel_end = layout.kernel_end as u64;
+        let ini1_base = kernel_meta.ini1_base;
+        let loader_base = kernel_meta.loader_base;
+        if ini1_base < kernel_end {
+            bail!("INI1 base {ini1_base:#X} overlaps the kernel ending at {kernel_end:#X}");
+        }
+        if loader_base < ini1_base {
+            bail!("Loader base {loader_base:#X} precedes INI1 base {ini1_base:#X}");
+        }
+        if loader_base >= image.len() as u64 {
+            bail!("Loader base {loader_base:#X} is out of bounds of the image");
+        }
+        let (ini1_base, loader_base) = (ini1_base as usize, loader_base as usize);
+
+        // Parse the INI1 header, if the image has KIPs at all.
+        let mut kips = Vec::new();
+        let ini1_header = Ini1Header::read(&image[ini1_base..])
+            .ok()
+            .filter(|h| h.magic == u32::from_le_bytes(*INI1_MAGIC));
+        let ini1_size = match ini1_header {
+            Some(header) => {
+                let size = header.size as usize;
+                let count = header.num_processes;
+
+                let ini1_end = ini1_base + size;
+                if size < header.size() {
+                    bail!("INI1 of {size:#X} bytes is smaller than its header");
+                }
+                if ini1_end > loader_base {
+                    bail!("INI1 of {size:#X} bytes overlaps the loader");
+                }
+                if count > MAX_KIP_COUNT as u32 {
+                    bail!("INI1 holds {count} KIPs, only {MAX_KIP_COUNT} are allowed");
+                }
+
+                // Split the record into the individual KIP binaries.
+                let mut offset = ini1_base + header.size();
+                for i in 0..count {
+                    let kip_end = kip_size(&image[offset..ini1_end])
+                        .map(|kip_size| offset + kip_size)
+                        .filter(|&kip_end| kip_end <= ini1_end);
+                    match kip_end {
+                        Some(kip_end) => {
+                            kips.push(offset..kip_end);
+                            offset = kip_end;
+                        }
+                        None => bail!("KIP {i} at {offset:#X} is malformed"),
+                    }
+                }
+                if offset != ini1_end {
+                    bail!("INI1 has {:#X} bytes of trailing data", ini1_end - offset);
+                }
+
+                size
+            }
+            None => 0,
+        };
+
+        // Locate the loader metadata and cross-check it.
+        let loader = &image[loader_base..];
+        let loader_meta_offset = find_meta_offset(loader, KERNEL_LOADER_MAGIC)?;
+        let loader_meta = KernelLoaderMeta::read(&loader[loader_meta_offset..])?;
+        if loader_meta.marker != 0xCCCCCCCC {
+            bail!("Loader marker mismatch: got {:#X}", loader_meta.marker);
+        }
+        if loader_meta.version != kernel_meta.version {
+            bail!(
+                "Kernel version {:#X} does not match loader version {:#X}",
+                kernel_meta.version,
+                loader_meta.version
+            );
+        }
+
+        // The build info, if any, is placed right after the loader.
+        let loader_end = loader_base + loader_meta.size as usize;
+        if loader_end > image.len() || loader_end < loader_base + loader_meta_offset {
+            bail!(
+                "Loader size {:#X} is inconsistent with the image",
+                loader_meta.size
+            );
+        }
+        let build_info = read_build_info_at(&image, loader_end)?;
+
+        Ok(Self {
+            image,
+            kernel_meta: (kernel_meta_offset, kernel_meta),
+            loader_meta: (loader_meta_offset, loader_meta),
+
+            ini1_size,
+            kips,
+
+            loader_end,
+            build_info,
+        })
+    }
+
+    /// Gets the raw bytes of the whole Kernel Image.
+    pub fn image(&self) -> &[u8] {
+        &self.image
+    }
+
+    /// Gets the metadata of the Kernel.
+    pub fn kernel_meta(&self) -> &KernelMeta {
+        &self.kernel_meta.1
+    }
+
+    /// Gets the metadata of the Kernel Loader.
+    pub fn loader_meta(&self) -> &KernelLoaderMeta {
+        &self.loader_meta.1
+    }
+
+    /// Gets the `(major, minor, micro)` version of the image.
+    pub fn version(&self) -> (u8, u8, u8) {
+        unpack_version(self.kernel_meta.1.version)
+    }
+
+    /// Gets the [`Profile`] the image was built with.
+    pub fn profile(&self) -> Profile {
+        Profile::from_version(self.kernel_meta.1.version)
+    }
+
+    /// Gets the Kernel binary, including its tail up to
+    /// [`KernelLayout::kernel_end`][crate::KernelLayout::kernel_end].
+    ///
+    /// The tail is filled with the padding byte the image was built
+    /// with, see [`ImageBuilder::with_padding_byte`][crate::ImageBuilder::with_padding_byte].
+    pub fn kernel(&self) -> &[u8] {
+        &self.image[..self.kernel_meta.1.layout.kernel_end as usize]
+    }
+
+    /// Gets the INI1 record, including its header.
+    ///
+    /// This is empty for images without any KIPs.
+    pub fn ini1(&self) -> &[u8] {
+        let base = self.kernel_meta.1.ini1_base as usize;
+        &self.image[base..base + self.ini1_size]
+    }
+
+    /// Gets the size of the INI1 record in bytes, including its
+    /// header.
+    ///
+    /// This is zero for images without any KIPs.
+    pub fn ini1_size(&self) -> usize {
+        self.ini1_size
+    }
+
+    /// Gets an iterator over the KIP binaries in the INI1 record.
+    pub fn kips(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.kips.iter().map(|kip| &self.image[kip.clone()])
+    }
+
+    /// Gets the number of KIPs in the INI1 record.
+    pub fn kip_count(&self) -> u8 {
+        self.kips.len() as u8
+    }
+
+    /// Gets the Kernel Loader binary.
+    ///
+    /// When the image carries no [`BuildInfo`], the trailing
+    /// padding of the image will be part of this slice.
+    pub fn loader(&self) -> &[u8] {
+        &self.image[self.kernel_meta.1.loader_base as usize..self.loader_end]
+    }
+
+    /// Gets the [`BuildInfo`] embedded in the image, if any.
+    pub fn build_info(&self) -> Option<&BuildInfo> {
+        self.build_info.as_ref()
+    }
+}
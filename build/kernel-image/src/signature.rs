@@ -0,0 +1,55 @@
+use std::{fs, io, mem::size_of, path::Path};
+
+use anyhow::{bail, Result};
+use byteorder::{ByteOrder, WriteBytesExt, LE};
+
+/// The magic value which ends a signed Kernel Image.
+pub const SIGNATURE_MAGIC: &[u8; 4] = b"SSG0";
+
+// The trailer consists of the signature length and the magic.
+const TRAILER_SIZE: usize = size_of::<u32>() + SIGNATURE_MAGIC.len();
+
+/// Reads a signed Kernel Image from the given path and splits it
+/// into the image body and its signature.
+///
+/// The body is exactly what the signing function passed to
+/// [`ImageBuilder::finalize_signed`][crate::ImageBuilder::finalize_signed]
+/// received, so the signature can be verified against it.
+///
+/// Returns [`None`] if the image is not signed.
+pub fn read_signature<P: AsRef<Path>>(path: P) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut image = fs::read(path)?;
+    match split_signature(&image)? {
+        Some(body_len) => {
+            let mut signature = image.split_off(body_len);
+            signature.truncate(signature.len() - TRAILER_SIZE);
+            Ok(Some((image, signature)))
+        }
+        None => Ok(None),
+    }
+}
+
+// Appends `signature` along with its trailer to `writer`.
+pub(crate) fn write_signature<W: io::Write>(writer: &mut W, signature: &[u8]) -> io::Result<()> {
+    writer.write_all(signature)?;
+    writer.write_u32::<LE>(signature.len() as u32)?;
+    writer.write_all(SIGNATURE_MAGIC)?;
+
+    Ok(())
+}
+
+// Determines the length of the body of a signed image. The
+// trailer is at the very end, so it can be found without
+// parsing the image itself.
+fn split_signature(image: &[u8]) -> Result<Option<usize>> {
+    if image.len() < TRAILER_SIZE || !image.ends_with(SIGNATURE_MAGIC) {
+        return Ok(None);
+    }
+
+    let trailer = image.len() - TRAILER_SIZE;
+    let signature_len = LE::read_u32(&image[trailer..]) as usize;
+    match trailer.checked_sub(signature_len) {
+        Some(body_len) => Ok(Some(body_len)),
+        None => bail!("Signature of {signature_len:#X} bytes exceeds the image"),
+    }
+}
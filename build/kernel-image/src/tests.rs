@@ -0,0 +1,421 @@
+use std::{env, fs, path::PathBuf};
+
+use super::*;
+
+// Creates a minimal Kernel binary which claims to end at 0x300.
+fn kernel() -> Vec<u8> {
+    let mut kernel = vec![0xAA; 4];
+    let mut meta = KernelMeta {
+        magic: u32::from_le_bytes(*KERNEL_MAGIC),
+        ..Default::default()
+    };
+    meta.layout.kernel_end = 0x300;
+    meta.write(&mut kernel).unwrap();
+    kernel.resize(0x280, 0xAB);
+    kernel
+}
+
+// Creates a minimal Kernel Loader binary.
+fn loader() -> Vec<u8> {
+    let mut loader = vec![0xBB; 4];
+    KernelLoaderMeta {
+        magic: u32::from_le_bytes(*KERNEL_LOADER_MAGIC),
+        version: 0,
+        marker: 0xCCCCCCCC,
+        size: 0,
+    }
+    .write(&mut loader)
+    .unwrap();
+    loader.extend([0x11; 0x50]);
+    loader
+}
+
+// Creates a KIP binary named `name` with a text segment of `size` bytes.
+fn kip(name: &str, size: u32) -> Vec<u8> {
+    let mut kip = vec![0; KIP_HEADER_SIZE];
+    kip[..4].copy_from_slice(KIP_MAGIC);
+    kip[0x4..0x4 + name.len()].copy_from_slice(name.as_bytes());
+    kip[0x28..0x2C].copy_from_slice(&size.to_le_bytes());
+    kip[0x48..0x4C].copy_from_slice(&3u32.to_le_bytes());
+    kip.extend((0..size + 3).map(|_| size as u8));
+    kip
+}
+
+fn builder() -> ImageBuilder {
+    ImageBuilder::default()
+        .with_page_size(0x1000)
+        .unwrap()
+        .with_kernel_bytes(kernel())
+        .unwrap()
+        .with_loader_bytes(loader())
+        .unwrap()
+}
+
+// Gets a path in the temporary directory which is unique to the test.
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("kernel-image-{}-{name}", std::process::id()))
+}
+
+// Finalizes `builder` into a temporary file and reads it back.
+fn finalize_to_vec(builder: ImageBuilder, name: &str) -> Vec<u8> {
+    let path = temp_path(name);
+    builder.finalize(&path).unwrap();
+
+    let image = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    image
+}
+
+#[test]
+fn kernel_layout_field_offsets() {
+    let layout = KernelLayout {
+        text_start: 0x00,
+        text_end: 0x04,
+        rodata_start: 0x08,
+        rodata_end: 0x0C,
+        data_start: 0x10,
+        data_end: 0x14,
+        bss_start: 0x18,
+        bss_end: 0x1C,
+        kernel_end: 0x20,
+        dynamic_start: 0x24,
+    };
+    let mut bytes = Vec::new();
+    layout.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 0x28);
+
+    // Every field holds its own offset, which must match the
+    // offsets asserted for `libkern::layout::KernelLayout`.
+    for (offset, field) in bytes.chunks_exact(4).enumerate() {
+        assert_eq!(field, (offset as u32 * 4).to_le_bytes());
+    }
+    assert_eq!(KernelLayout::read(&bytes).unwrap(), layout);
+}
+
+#[test]
+fn round_trip() {
+    let path = temp_path("round-trip.bin");
+    let stats = builder()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .add_kip_bytes(kip("PM", 9))
+        .unwrap()
+        .with_version(1, 2, 3)
+        .finalize(&path)
+        .unwrap();
+
+    let reader = ImageReader::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(reader.image().len(), stats.total_size);
+    assert_eq!(reader.version(), (1, 2, 3));
+
+    // The metadata is updated during the build, so only compare the
+    // code around it.
+    let (kernel, loader) = (kernel(), loader());
+    assert_eq!(reader.kernel().len(), 0x300);
+    assert_eq!(reader.kernel()[..4], kernel[..4]);
+    assert_eq!(reader.kernel()[0x100..0x280], kernel[0x100..]);
+    assert_eq!(reader.loader().len(), loader.len());
+    assert_eq!(reader.loader()[..4], loader[..4]);
+    assert_eq!(
+        reader.loader()[loader.len() - 0x50..loader.len()],
+        loader[loader.len() - 0x50..]
+    );
+
+    let kips: Vec<_> = reader.kips().collect();
+    assert_eq!(kips, [&kip("FS", 5)[..], &kip("PM", 9)[..]]);
+}
+
+#[test]
+fn reject_undersized_ini1() {
+    let path = temp_path("undersized-ini1.bin");
+    builder()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .finalize(&path)
+        .unwrap();
+    let mut image = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // Shrink the INI1 size below the size of its own header.
+    let ini1_base = image.windows(4).position(|w| w == INI1_MAGIC).unwrap();
+    image[ini1_base + 4..ini1_base + 8].copy_from_slice(&8u32.to_le_bytes());
+
+    assert!(ImageReader::new(image).is_err());
+}
+
+#[test]
+fn reject_unaligned_load_address() {
+    assert!(builder().with_load_address(0x8000_0800).validate().is_err());
+
+    let path = temp_path("unaligned-load-address.elf");
+    assert!(builder()
+        .with_load_address(0x8000_0800)
+        .finalize_elf(&path)
+        .is_err());
+    assert!(!path.exists());
+
+    assert!(builder().with_load_address(0x8000_1000).validate().is_ok());
+}
+
+#[test]
+fn truncate_existing_output() {
+    let path = temp_path("truncate.bin");
+    builder()
+        .add_kip_bytes(kip("FS", 0x2000))
+        .unwrap()
+        .finalize(&path)
+        .unwrap();
+    let large = fs::metadata(&path).unwrap().len();
+
+    let stats = builder().finalize(&path).unwrap();
+    let small = fs::metadata(&path).unwrap().len();
+    fs::remove_file(&path).unwrap();
+
+    assert!(small < large);
+    assert_eq!(small as usize, stats.total_size);
+}
+
+#[test]
+fn kips_from_dir_in_name_order() {
+    let kips = [
+        ("a.kip", kip("FS", 5)),
+        ("b.kip", kip("PM", 9)),
+        ("c.kip", kip("SM", 2)),
+    ];
+
+    // Populate two directories in opposite orders, so the result
+    // cannot follow the creation order by accident.
+    let forward = temp_path("kips-forward");
+    let backward = temp_path("kips-backward");
+    for (dir, order) in [(&forward, [0, 1, 2]), (&backward, [2, 1, 0])] {
+        fs::create_dir_all(dir).unwrap();
+        for i in order {
+            fs::write(dir.join(kips[i].0), &kips[i].1).unwrap();
+        }
+    }
+
+    let from_forward = builder().add_kips_from_dir(&forward).unwrap();
+    let from_backward = builder().add_kips_from_dir(&backward).unwrap();
+    fs::remove_dir_all(&forward).unwrap();
+    fs::remove_dir_all(&backward).unwrap();
+
+    let expected: Vec<_> = kips.into_iter().map(|(_, kip)| kip).collect();
+    assert_eq!(from_forward.kips, expected);
+    assert_eq!(from_backward.kips, expected);
+}
+
+#[test]
+fn loader_placement() {
+    // (ini1_start, ini1_len, has_ini1, page_size, loader_start)
+    let cases = [
+        (0x1000, 0x100, true, 0x1000, 0x2000),
+        (0x1000, 0x1000, true, 0x1000, 0x2000),
+        (0x1000, 0x1001, true, 0x1000, 0x3000),
+        (0x4000, 0x10, true, 0x4000, 0x8000),
+        (0x1000, 0, false, 0x1000, 0x2000),
+        (0x4000, 0, false, 0x4000, 0x8000),
+    ];
+
+    for (ini1_start, ini1_len, has_ini1, page_size, expected) in cases {
+        let ini1_end = ini1_start + ini1_len;
+        let loader_start = place_loader(ini1_end, has_ini1, page_size);
+        assert_eq!(
+            loader_start, expected,
+            "INI1 at {ini1_start:#X}..{ini1_end:#X}"
+        );
+        assert_eq!(loader_start % page_size, 0);
+
+        // The loader never overlaps INI1, and never shares its
+        // offset with an empty one.
+        assert!(loader_start >= ini1_end);
+        assert!(loader_start > ini1_start);
+    }
+}
+
+#[test]
+fn kip_count_limit() {
+    let mut builder = builder();
+    for i in 0..MAX_KIP_COUNT {
+        builder = builder.add_kip_bytes(kip(&format!("K{i:02X}"), 1)).unwrap();
+    }
+    assert_eq!(builder.kips.len(), MAX_KIP_COUNT as usize);
+    assert!(builder.validate().is_ok());
+
+    assert!(builder.add_kip_bytes(kip("EXTRA", 1)).is_err());
+}
+
+#[test]
+fn deterministic_builds() {
+    let build = |timestamp, name| {
+        let path = temp_path(name);
+        builder()
+            .add_kip_bytes(kip("FS", 5))
+            .unwrap()
+            .with_build_info(BuildInfo {
+                commit: "abcdef".into(),
+                timestamp,
+                builder_version: "0.1.0".into(),
+            })
+            .deterministic()
+            .finalize(&path)
+            .unwrap();
+
+        let image = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        image
+    };
+
+    assert_eq!(
+        build(1, "deterministic-1.bin"),
+        build(2, "deterministic-2.bin")
+    );
+}
+
+#[test]
+fn replace_duplicate_kips() {
+    let builder = builder()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .add_kip_bytes(kip("PM", 9))
+        .unwrap()
+        .add_kip_bytes(kip("FS", 7))
+        .unwrap();
+
+    // The duplicate takes the place of the KIP it replaces.
+    assert_eq!(builder.kips, [kip("FS", 7), kip("PM", 9)]);
+    assert!(builder.validate().is_ok());
+}
+
+#[test]
+fn reject_duplicate_kips_when_strict() {
+    let strict = builder()
+        .with_strict_kips()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap();
+    assert!(strict.add_kip_bytes(kip("FS", 7)).is_err());
+
+    // Duplicates that were replaced before the builder was made
+    // strict are still caught during validation.
+    let lenient = builder()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .add_kip_bytes(kip("FS", 7))
+        .unwrap();
+    assert!(lenient.with_strict_kips().validate().is_err());
+}
+
+#[test]
+fn mismatched_kip_sizes() {
+    let mut undersized = kip("FS", 5);
+    undersized.pop();
+    let mut oversized = kip("PM", 5);
+    oversized.push(0);
+
+    // By default, mismatched KIPs are only warned about.
+    let lenient = builder()
+        .add_kip_bytes(undersized.clone())
+        .unwrap()
+        .add_kip_bytes(oversized.clone())
+        .unwrap();
+    assert_eq!(lenient.kips, [undersized.clone(), oversized.clone()]);
+    assert!(lenient.validate().is_ok());
+
+    let strict = || builder().with_strict_kips();
+    assert!(strict().add_kip_bytes(undersized).is_err());
+    assert!(strict().add_kip_bytes(oversized).is_err());
+    assert!(strict().add_kip_bytes(kip("FS", 5)).is_ok());
+
+    // KIPs added before the builder was made strict are still
+    // caught during validation.
+    assert!(lenient.with_strict_kips().validate().is_err());
+}
+
+#[test]
+fn build_info_after_loader() {
+    let info = BuildInfo {
+        commit: "abcdef".into(),
+        timestamp: 1,
+        builder_version: "0.1.0".into(),
+    };
+
+    // A KIP which happens to contain the build info magic must not
+    // be mistaken for it.
+    let mut fake = kip("FS", 0x20);
+    fake[KIP_HEADER_SIZE..KIP_HEADER_SIZE + 4].copy_from_slice(BUILD_INFO_MAGIC);
+    let builder = || builder().add_kip_bytes(fake.clone()).unwrap();
+
+    let path = temp_path("build-info.bin");
+    builder().finalize(&path).unwrap();
+    let without = read_build_info(&path);
+    builder()
+        .with_build_info(info.clone())
+        .finalize(&path)
+        .unwrap();
+    let with = read_build_info(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(without.unwrap(), None);
+    assert_eq!(with.unwrap(), Some(info));
+}
+
+#[test]
+fn read_version_round_trip() {
+    let path = temp_path("version.bin");
+    builder().with_version(3, 14, 15).finalize(&path).unwrap();
+
+    let version = read_version(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(version.unwrap(), (3, 14, 15));
+}
+
+#[test]
+fn profile_round_trip() {
+    for profile in [Profile::Debug, Profile::Release] {
+        let path = temp_path("profile.bin");
+        builder()
+            .with_version(3, 14, 15)
+            .with_build_profile(profile)
+            .finalize(&path)
+            .unwrap();
+
+        let reader = ImageReader::open(&path).unwrap();
+        let version = read_version(&path);
+        fs::remove_file(&path).unwrap();
+
+        // The profile flag must not leak into the version.
+        assert_eq!(reader.profile(), profile);
+        assert_eq!(reader.version(), (3, 14, 15));
+        assert_eq!(version.unwrap(), (3, 14, 15));
+    }
+}
+
+#[test]
+fn builder_order_independent() {
+    let kernel_first = ImageBuilder::default()
+        .with_page_size(0x1000)
+        .unwrap()
+        .with_kernel_bytes(kernel())
+        .unwrap()
+        .with_loader_bytes(loader())
+        .unwrap()
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .with_version(1, 2, 3);
+    let loader_first = ImageBuilder::default()
+        .with_version(1, 2, 3)
+        .add_kip_bytes(kip("FS", 5))
+        .unwrap()
+        .with_loader_bytes(loader())
+        .unwrap()
+        .with_kernel_bytes(kernel())
+        .unwrap()
+        .with_page_size(0x1000)
+        .unwrap();
+
+    let kernel_first = finalize_to_vec(kernel_first, "kernel-first.bin");
+    let loader_first = finalize_to_vec(loader_first, "loader-first.bin");
+    assert_eq!(kernel_first, loader_first);
+    assert_eq!(ImageReader::new(loader_first).unwrap().version(), (1, 2, 3));
+}
@@ -1,8 +1,12 @@
 //! Implementation of the build system action `build`.
 
-use std::{io::BufReader, path::PathBuf};
+use std::{
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use xshell::{cmd, Shell};
 
 use crate::{
@@ -16,14 +20,50 @@ use crate::{
 ///
 /// The resulting binary will include both the Kernel, the Kernel
 /// Loader and all the Kernel Initial Processes (KIPs).
-pub fn build_kernel(sh: &Shell, target: &Target, release: bool) -> Result<PathBuf> {
-    let kernel_loader = build(sh, &package::KERNEL_LOADER, target, release)?;
-    let kernel = build(sh, &package::KERNEL, target, release)?;
+///
+/// The Kernel is linked with `linker_script` when given, instead
+/// of the default script for the target architecture. The Kernel
+/// Loader always uses its default script.
+pub fn build_kernel(
+    sh: &Shell,
+    target: &Target,
+    release: bool,
+    linker_script: Option<&Path>,
+) -> Result<PathBuf> {
+    // Resolve the linker script before the working directory changes.
+    let linker_script = linker_script
+        .map(|script| {
+            script
+                .canonicalize()
+                .with_context(|| format!("Linker script {} does not exist", script.display()))
+        })
+        .transpose()?;
+
+    let kernel_loader = build(sh, &package::KERNEL_LOADER, target, release, None)?;
+    let kernel = build(
+        sh,
+        &package::KERNEL,
+        target,
+        release,
+        linker_script.as_deref(),
+    )?;
 
     let version_major = env!("CARGO_PKG_VERSION_MAJOR").parse()?;
     let version_minor = env!("CARGO_PKG_VERSION_MINOR").parse()?;
     let version_patch = env!("CARGO_PKG_VERSION_PATCH").parse()?;
 
+    let build_info = kernel_image::BuildInfo {
+        commit: git_commit(sh),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        builder_version: env!("CARGO_PKG_VERSION").to_owned(),
+    };
+
+    let profile = if release {
+        kernel_image::Profile::Release
+    } else {
+        kernel_image::Profile::Debug
+    };
+
     let image_path = {
         let mut root = rustc::project_root();
         root.push("target");
@@ -37,16 +77,52 @@ pub fn build_kernel(sh: &Shell, target: &Target, release: bool) -> Result<PathBu
 
     // TODO: Add support for baking in KIPs.
     kernel_image::ImageBuilder::default()
-        .with_page_size(target.config.page_size as usize)
+        .with_page_size(target.config.page_size)?
         .with_loader(kernel_loader)?
         .with_kernel(kernel)?
         .with_version(version_major, version_minor, version_patch)
+        .with_build_profile(profile)
+        .with_build_info(build_info)
+        .with_map_file(image_path.with_extension("map"))
         .finalize(&image_path)?;
 
     Ok(image_path)
 }
 
-fn build(sh: &Shell, pkg: &Package, target: &Target, release: bool) -> Result<PathBuf> {
+// Gets the commit hash of the checked out sources, if available.
+// Building from a source tarball without git metadata is fine.
+fn git_commit(sh: &Shell) -> String {
+    let _cwd = sh.push_dir(rustc::project_root());
+    cmd!(sh, "git rev-parse HEAD")
+        .quiet()
+        .ignore_stderr()
+        .read()
+        .unwrap_or_default()
+}
+
+fn build(
+    sh: &Shell,
+    pkg: &Package,
+    target: &Target,
+    release: bool,
+    linker_script: Option<&Path>,
+) -> Result<PathBuf> {
+    let elf = build_elf(sh, pkg, target, release, linker_script)?;
+    make_raw_binary(sh, elf)
+}
+
+/// Builds a single binary package and returns the path to the
+/// resulting ELF file.
+///
+/// The package is linked with `linker_script` when given, and
+/// with the default script for the target architecture otherwise.
+pub fn build_elf(
+    sh: &Shell,
+    pkg: &Package,
+    target: &Target,
+    release: bool,
+    linker_script: Option<&Path>,
+) -> Result<PathBuf> {
     let _cwd = sh.push_dir(rustc::project_root());
 
     let release_arg = if release { &["--release"][..] } else { &[] };
@@ -56,7 +132,11 @@ fn build(sh: &Shell, pkg: &Package, target: &Target, release: bool) -> Result<Pa
 
     // Before we start, copy the requested linker script over.
     // This is done to establish a standard target jsons can refer to.
-    let linker_script = format!("build/linker-scripts/{}-{}.ld", target.arch, pkg.name);
+    // Overrides take the same path, so no extra RUSTFLAGS are needed.
+    let linker_script = match linker_script {
+        Some(script) => script.to_owned(),
+        None => format!("build/linker-scripts/{}-{}.ld", target.arch, pkg.name).into(),
+    };
     sh.copy_file(linker_script, "link.ld")?;
 
     // Build the requested package using cargo.
@@ -77,11 +157,8 @@ fn build(sh: &Shell, pkg: &Package, target: &Target, release: bool) -> Result<Pa
     sh.remove_path("link.ld")?;
 
     // Try to extract the produced ELF binary for successful builds.
-    let artifact_path = extract_build_artifact(&output.stdout)
-        .ok_or_else(|| anyhow!("Build failed! Please run the `check` subcommand for details"))?;
-
-    // Convert to raw binary and return the path to it.
-    make_raw_binary(sh, artifact_path)
+    extract_build_artifact(&output.stdout)
+        .ok_or_else(|| anyhow!("Build failed! Please run the `check` subcommand for details"))
 }
 
 fn extract_build_artifact(rustc_output: &[u8]) -> Option<PathBuf> {
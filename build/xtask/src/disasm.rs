@@ -0,0 +1,100 @@
+//! Implementation of the build system action `disasm`.
+
+use std::{ops::Range, path::Path};
+
+use anyhow::{bail, Result};
+use xshell::{cmd, Shell};
+
+use crate::{build, package::Package, rustc, target::Target};
+
+/// What to disassemble from a package's ELF binary.
+#[derive(Clone, Debug)]
+pub enum Selection {
+    /// A function or object by its name in the symbol table.
+    Symbol(String),
+    /// A raw range of addresses.
+    Range(Range<u64>),
+}
+
+/// Builds the given package and prints the disassembly of the
+/// selected part of its ELF binary.
+///
+/// Symbols are looked up by their demangled name, with or without
+/// the trailing hash of Rust's legacy mangling scheme.
+pub fn disasm(
+    sh: &Shell,
+    pkg: &Package,
+    target: &Target,
+    selection: &Selection,
+    release: bool,
+) -> Result<()> {
+    let elf = build::build_elf(sh, pkg, target, release, None)?;
+
+    let range = match selection {
+        Selection::Symbol(symbol) => resolve_symbol(sh, &elf, symbol)?,
+        Selection::Range(range) => range.clone(),
+    };
+    let Range { start, end } = range;
+    if start >= end {
+        bail!("Address range {start:#X}..{end:#X} is empty");
+    }
+
+    let objdump = rustc::llvm_binutil(sh, "objdump")?;
+    let range_args = [
+        format!("--start-address={start:#x}"),
+        format!("--stop-address={end:#x}"),
+    ];
+    cmd!(
+        sh,
+        "{objdump} -d -C --no-show-raw-insn {range_args...} {elf}"
+    )
+    .run()?;
+
+    Ok(())
+}
+
+// Finds the address range of `symbol` in the symbol table of `elf`.
+fn resolve_symbol(sh: &Shell, elf: &Path, symbol: &str) -> Result<Range<u64>> {
+    let nm = rustc::llvm_binutil(sh, "nm")?;
+    let symbols = cmd!(sh, "{nm} -C -S --defined-only {elf}").quiet().read()?;
+
+    // Every line is formatted as `<address> <size> <type> <name>`,
+    // where symbols without a known size are missing the size.
+    let mut matches = symbols.lines().filter_map(|line| {
+        let mut parts = line.splitn(4, ' ');
+        let address = parts.next()?;
+        let size = parts.next().filter(|size| size.len() == address.len())?;
+        let name = parts.nth(1)?;
+
+        let address = u64::from_str_radix(address, 16).ok()?;
+        let size = u64::from_str_radix(size, 16).ok()?;
+
+        symbol_matches(name, symbol).then(|| address..address + size)
+    });
+
+    match (matches.next(), matches.next()) {
+        (Some(range), None) => Ok(range),
+        (Some(_), Some(_)) => bail!("Symbol `{symbol}` is ambiguous"),
+        (None, _) => bail!("Symbol `{symbol}` not found or has no size"),
+    }
+}
+
+fn symbol_matches(name: &str, symbol: &str) -> bool {
+    name == symbol
+        || name
+            .rsplit_once("::h")
+            .map_or(false, |(name, hash)| name == symbol && hash.len() == 16)
+}
+
+/// Parses an address range given as `start:end` in hexadecimal.
+pub fn parse_range(range: &str) -> Result<Range<u64>, String> {
+    let parse = |addr: &str| {
+        let addr = addr.trim_start_matches("0x").trim_start_matches("0X");
+        u64::from_str_radix(addr, 16).map_err(|e| e.to_string())
+    };
+
+    let (start, end) = range
+        .split_once(':')
+        .ok_or_else(|| "range must be given as start:end!".to_owned())?;
+    Ok(parse(start)?..parse(end)?)
+}
@@ -2,8 +2,11 @@
 
 pub mod build;
 pub mod check;
+pub mod disasm;
 pub mod lint;
 pub mod package;
 pub mod run;
 pub mod rustc;
 pub mod target;
+pub mod test;
+pub mod verify;
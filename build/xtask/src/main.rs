@@ -1,8 +1,11 @@
+use std::{ops::Range, path::PathBuf};
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use xshell::Shell;
 use xtask::{
-    package::{all_packages, Package},
+    disasm::{parse_range, Selection},
+    package::{all_packages, packages_with_tag, test_packages, Package},
     target::*,
 };
 
@@ -24,12 +27,20 @@ enum Action {
         /// Invokes cargo in release mode.
         #[clap(short, long)]
         release: bool,
+
+        /// Links the kernel with a custom linker script.
+        #[clap(long)]
+        linker_script: Option<PathBuf>,
     },
 
     /// Builds the kernel binaries to check for warnings/errors.
     Check {
-        #[clap(parse(try_from_str=parse_package))]
-        package: Package,
+        #[clap(parse(try_from_str=parse_package), required_unless_present = "group")]
+        package: Option<Package>,
+
+        /// Checks all packages tagged with the given group instead.
+        #[clap(long, short, parse(try_from_str=parse_group), conflicts_with = "package")]
+        group: Option<String>,
 
         /// Invokes cargo in release mode.
         #[clap(short, long)]
@@ -48,6 +59,45 @@ enum Action {
         /// Pass the `--check` flag to rustfmt.
         #[clap(short, long)]
         check: bool,
+
+        /// Only lints the packages tagged with the given group.
+        #[clap(long, short, parse(try_from_str=parse_group))]
+        group: Option<String>,
+    },
+
+    /// Runs the unit tests of all packages which support the host.
+    Test {
+        /// Only tests the given package.
+        #[clap(parse(try_from_str=parse_test_package))]
+        package: Option<Package>,
+
+        /// Invokes cargo in release mode.
+        #[clap(short, long)]
+        release: bool,
+    },
+
+    /// Builds a package and disassembles a symbol or address range.
+    Disasm {
+        #[clap(parse(try_from_str=parse_package))]
+        package: Package,
+
+        /// The name of the symbol to disassemble.
+        #[clap(required_unless_present = "range")]
+        symbol: Option<String>,
+
+        /// Disassembles the hexadecimal address range `start:end` instead.
+        #[clap(long, parse(try_from_str=parse_range), conflicts_with = "symbol")]
+        range: Option<Range<u64>>,
+
+        /// Invokes cargo in release mode.
+        #[clap(short, long)]
+        release: bool,
+    },
+
+    /// Validates an existing kernel image and prints its layout.
+    Verify {
+        /// Path to the kernel image to inspect.
+        path: PathBuf,
     },
 }
 
@@ -56,21 +106,60 @@ fn main() -> Result<()> {
 
     let shell = Shell::new()?;
     match cli.action {
-        Action::Build { release } => {
-            let kernel = xtask::build::build_kernel(&shell, &cli.target, release)?;
+        Action::Build {
+            release,
+            linker_script,
+        } => {
+            let kernel =
+                xtask::build::build_kernel(&shell, &cli.target, release, linker_script.as_deref())?;
             shell.copy_file(kernel, xtask::rustc::project_root())?;
             Ok(())
         }
 
-        Action::Check { package, release } => {
-            xtask::check::check(&shell, &package, &cli.target, release)
-        }
+        Action::Check {
+            package,
+            group,
+            release,
+        } => select_packages(package, group)
+            .iter()
+            .try_for_each(|p| xtask::check::check(&shell, p, &cli.target, release)),
 
         Action::Run { release } => xtask::run::run(&shell, &cli.target, release),
 
-        Action::Lint { check } => {
-            all_packages().try_for_each(|p| xtask::lint::lint(&shell, p, &cli.target, check))
+        Action::Lint { check, group } => select_packages(None, group)
+            .iter()
+            .try_for_each(|p| xtask::lint::lint(&shell, p, &cli.target, check)),
+
+        Action::Test { package, release } => match package {
+            Some(package) => xtask::test::test(&shell, &package, release),
+            None => test_packages().try_for_each(|p| xtask::test::test(&shell, p, release)),
+        },
+
+        Action::Disasm {
+            package,
+            symbol,
+            range,
+            release,
+        } => {
+            let selection = match (symbol, range) {
+                (Some(symbol), _) => Selection::Symbol(symbol),
+                (None, Some(range)) => Selection::Range(range),
+                (None, None) => unreachable!(),
+            };
+            xtask::disasm::disasm(&shell, &package, &cli.target, &selection, release)
         }
+
+        Action::Verify { path } => xtask::verify::verify(&path, &cli.target),
+    }
+}
+
+/// Selects either a single package, all packages in a group, or
+/// every package if neither is given.
+fn select_packages(package: Option<Package>, group: Option<String>) -> Vec<Package> {
+    match (package, group) {
+        (Some(package), _) => vec![package],
+        (None, Some(group)) => packages_with_tag(&group).copied().collect(),
+        (None, None) => all_packages().copied().collect(),
     }
 }
 
@@ -87,3 +176,18 @@ fn parse_package(package: &str) -> Result<Package, String> {
         .copied()
         .ok_or_else(|| "package does not exist!".into())
 }
+
+fn parse_test_package(package: &str) -> Result<Package, String> {
+    test_packages()
+        .find(|p| p.name.eq_ignore_ascii_case(package))
+        .copied()
+        .ok_or_else(|| "package has no host-runnable tests!".into())
+}
+
+fn parse_group(group: &str) -> Result<String, String> {
+    if packages_with_tag(group).next().is_some() {
+        Ok(group.to_owned())
+    } else {
+        Err("no packages in group!".into())
+    }
+}
@@ -4,12 +4,52 @@
 pub const KERNEL: Package = Package {
     name: "kernel",
     cargo_name: "saturnus-kernel",
+    tags: &["bin", "kernel"],
+    host_tests: None,
 };
 
 /// The Kernel Loader package in the Saturnus workspace.
 pub const KERNEL_LOADER: Package = Package {
     name: "loader",
     cargo_name: "saturnus-kernel-loader",
+    tags: &["bin", "boot"],
+    host_tests: None,
+};
+
+/// The Kernel implementation details package in the Saturnus
+/// workspace.
+pub const LIBKERN: Package = Package {
+    name: "libkern",
+    cargo_name: "saturnus-libkern",
+    tags: &[],
+    host_tests: Some(&[]),
+};
+
+/// The shared utilities package in the Saturnus workspace.
+pub const UTILS: Package = Package {
+    name: "utils",
+    cargo_name: "saturnus-utils",
+    tags: &[],
+    host_tests: Some(&[]),
+};
+
+/// The semihosting package in the Saturnus workspace.
+///
+/// Its tests run against the `mock` backend, since the host
+/// cannot service semihosting traps.
+pub const SEMIHOSTING: Package = Package {
+    name: "semihosting",
+    cargo_name: "saturnus-semihosting",
+    tags: &[],
+    host_tests: Some(&["mock"]),
+};
+
+/// The Kernel Image tooling package in the Saturnus workspace.
+pub const KERNEL_IMAGE: Package = Package {
+    name: "kernel-image",
+    cargo_name: "kernel-image",
+    tags: &[],
+    host_tests: Some(&[]),
 };
 
 /// Definition of a Saturnus package to build.
@@ -21,9 +61,42 @@ pub struct Package {
     ///
     /// This will be passed as the `-p` argument during build.
     pub cargo_name: &'static str,
+    /// The groups this package belongs to.
+    ///
+    /// These can be used to select a subset of packages for
+    /// an action, see [`packages_with_tag`]. Only the packages
+    /// from [`all_packages`] can be selected this way.
+    pub tags: &'static [&'static str],
+    /// The cargo features to enable when running the package's
+    /// unit tests on the host.
+    ///
+    /// [`None`] for packages which cannot be built for the host.
+    pub host_tests: Option<&'static [&'static str]>,
 }
 
-/// Gets an iterator over all the Saturnus [`Package`]s.
+/// Gets an iterator over all the Saturnus [`Package`]s which are
+/// built for a target.
 pub fn all_packages() -> impl Iterator<Item = &'static Package> {
     [KERNEL, KERNEL_LOADER].iter()
 }
+
+/// Gets an iterator over all the Saturnus [`Package`]s which are
+/// tagged with `tag`.
+pub fn packages_with_tag(tag: &str) -> impl Iterator<Item = &'static Package> + '_ {
+    all_packages().filter(move |p| p.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+}
+
+/// Gets an iterator over all the Saturnus [`Package`]s which have
+/// unit tests that can be run on the host.
+pub fn test_packages() -> impl Iterator<Item = &'static Package> {
+    [
+        KERNEL,
+        KERNEL_LOADER,
+        LIBKERN,
+        UTILS,
+        SEMIHOSTING,
+        KERNEL_IMAGE,
+    ]
+    .iter()
+    .filter(|p| p.host_tests.is_some())
+}
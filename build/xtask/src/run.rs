@@ -1,8 +1,8 @@
 //! Implementation of the build system action `run`.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, process::Command};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use xshell::{cmd, Shell};
 
 use crate::{
@@ -14,15 +14,18 @@ use crate::{
 /// to emulate it in QEMU if the target supports it.
 ///
 /// The building step is delegated to [`build::build_kernel`].
+///
+/// Fails when the guest does not exit cleanly, i.e. when it
+/// reports a failure through the semihosting exit call.
 pub fn run(sh: &Shell, target: &Target, release: bool) -> Result<()> {
     let (system, extra_flags) = qemu_parts(target)?;
-    let raw = build::build_kernel(sh, target, release)?;
+    let raw = build::build_kernel(sh, target, release, None)?;
 
     run_qemu(sh, raw, system, extra_flags)
 }
 
 fn run_qemu(sh: &Shell, kernel: PathBuf, system: &str, extra_flags: &[&str]) -> Result<()> {
-    cmd!(
+    let qemu = cmd!(
         sh,
         "qemu-system-{system}
             {extra_flags...}
@@ -30,8 +33,14 @@ fn run_qemu(sh: &Shell, kernel: PathBuf, system: &str, extra_flags: &[&str]) ->
             -nographic
             -semihosting-config enable=on,target=native
             -kernel {kernel}"
-    )
-    .run()?;
+    );
 
-    Ok(())
+    // QEMU forwards the status which the guest passes to the
+    // semihosting exit call as its own exit code.
+    let status = Command::from(qemu).status()?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => bail!("Guest exited with failure status {code}"),
+        None => bail!("QEMU was terminated by a signal"),
+    }
 }
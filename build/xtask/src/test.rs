@@ -0,0 +1,40 @@
+//! Implementation of the build system action `test`.
+
+use anyhow::Result;
+use xshell::{cmd, Shell};
+
+use crate::{package::Package, rustc};
+
+/// Runs the unit tests of a given package on the host.
+///
+/// Packages which cannot be built for the host are skipped.
+pub fn test(sh: &Shell, pkg: &Package, release: bool) -> Result<()> {
+    let features = match pkg.host_tests {
+        Some(features) => features,
+        None => {
+            println!("Skipping {}: no host-runnable tests", pkg.name);
+            return Ok(());
+        }
+    };
+
+    let _cwd = sh.push_dir(rustc::project_root());
+
+    let release_arg = if release { &["--release"][..] } else { &[] };
+    let cargo_name = pkg.cargo_name;
+    let host = rustc_version::version_meta()?.host;
+    let features = features.iter().flat_map(|f| ["--features", *f]);
+
+    // Explicitly pass the host triple so that no bare-metal target
+    // configuration leaks into the test build.
+    cmd!(
+        sh,
+        "cargo test
+            {release_arg...}
+            -p {cargo_name}
+            --target {host}
+            {features...}"
+    )
+    .run()?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Implementation of the build system action `verify`.
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use kernel_image::ImageReader;
+
+use crate::target::Target;
+
+/// Validates an existing Saturnus kernel image at `path` and
+/// prints a concise report of its contents.
+///
+/// Structural validation is done by [`ImageReader`]; on top of
+/// that, all components must be aligned to the page size of the
+/// given [`Target`].
+pub fn verify(path: &Path, target: &Target) -> Result<()> {
+    let reader = ImageReader::open(path)?;
+    let meta = reader.kernel_meta();
+    let page_size = target.config.page_size as u64;
+
+    let (major, minor, micro) = reader.version();
+    let profile = reader.profile();
+    println!("{} ({:#X} bytes)", path.display(), reader.image().len());
+    println!("  Version: {major}.{minor}.{micro} ({profile:?})");
+    println!("  Kernel:  {:#010X}..{:#010X}", 0, meta.layout.kernel_end);
+    println!(
+        "  INI1:    {:#010X}..{:#010X} ({} KIPs)",
+        meta.ini1_base,
+        meta.ini1_base + reader.ini1_size() as u64,
+        reader.kip_count()
+    );
+    println!(
+        "  Loader:  {:#010X}..{:#010X}",
+        meta.loader_base,
+        meta.loader_base + reader.loader().len() as u64
+    );
+    if let Some(info) = reader.build_info() {
+        println!(
+            "  Build:   {} at {} (xtask {})",
+            info.commit, info.timestamp, info.builder_version
+        );
+    }
+
+    for (name, base) in [("INI1", meta.ini1_base), ("Loader", meta.loader_base)] {
+        if base % page_size != 0 {
+            bail!("{name} base {base:#X} is not aligned to page size {page_size:#X}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,11 @@
 //! Implementation details of [`crate::addr`].
 
-use core::{fmt, mem::size_of};
+use core::{fmt, iter::Step, mem::size_of};
 
-use utils::align;
+use utils::{align, bits::bitmask};
 
-pub const PHYS_ADDR_MASK: usize = 0xFFF0_0000_0000_0000;
-pub const VIRT_ADDR_MASK: usize = 0xFFFF_0000_0000_0000;
+pub const PHYS_ADDR_MASK: usize = !bitmask(0, 52);
+pub const VIRT_ADDR_MASK: usize = !bitmask(0, 48);
 
 macro_rules! impl_fmt_traits {
     (for $for:ident) => {
@@ -72,12 +72,37 @@ pub struct PhysAddr(*mut ());
 ///
 /// It ensures that the high 16 bits of its contained address
 /// are either all zeroes or all ones.
+///
+/// The non-canonical hole splits the address space into two
+/// halves. Stepping through addresses with [`Step`], e.g. when
+/// iterating over a range, never crosses the hole: any step which
+/// would leave the half it starts in returns [`None`], and so does
+/// [`Step::steps_between`] for addresses in different halves.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(transparent)]
 pub struct VirtAddr(*mut ());
 
+/// A [`PhysAddr`] was attempted to be wrapped into an [`Aligned`]
+/// object without satisfying its alignment.
+#[derive(Debug)]
+pub struct MisalignedAddress(usize);
+
+/// A [`PhysAddr`] which is statically known to be aligned to a
+/// multiple of `ALIGN`.
+///
+/// Functions which require aligned addresses may take this type
+/// instead of re-validating the alignment of a [`PhysAddr`] at
+/// runtime.
+///
+/// `ALIGN` must be a power of two, which is enforced when the
+/// type is constructed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Aligned<const ALIGN: usize>(PhysAddr);
+
 const _: () = assert!(size_of::<PhysAddr>() == size_of::<*mut ()>());
 const _: () = assert!(size_of::<VirtAddr>() == size_of::<*mut ()>());
+const _: () = assert!(size_of::<Aligned<0x1000>>() == size_of::<PhysAddr>());
 
 impl PhysAddr {
     /// Attempts to create a new physical address from the
@@ -359,7 +384,203 @@ impl VirtAddr {
     pub fn map_addr(self, f: impl FnOnce(usize) -> usize) -> Result<Self, InvalidAddress> {
         Self::try_new(self.0.map_addr(f))
     }
+
+    // Checks whether `addr` is in the same half of the address
+    // space as `self`, which also makes it canonical.
+    #[inline(always)]
+    fn is_same_half(self, addr: usize) -> bool {
+        (self.addr() ^ addr) & VIRT_ADDR_MASK == 0
+    }
+}
+
+impl<const ALIGN: usize> Aligned<ALIGN> {
+    // Referencing this rejects invalid alignments at compile time.
+    const ALIGN_MASK: usize = {
+        assert!(ALIGN.is_power_of_two());
+        ALIGN - 1
+    };
+
+    /// Attempts to wrap the supplied physical address.
+    ///
+    /// This will return [`MisalignedAddress`] when `addr` is not
+    /// aligned to a multiple of `ALIGN`.
+    #[inline(always)]
+    pub fn try_new(addr: PhysAddr) -> Result<Self, MisalignedAddress> {
+        if addr.addr() & Self::ALIGN_MASK == 0 {
+            Ok(Self(addr))
+        } else {
+            Err(MisalignedAddress(addr.addr()))
+        }
+    }
+
+    /// Wraps the supplied physical address.
+    ///
+    /// # Safety
+    ///
+    /// The address is not validated, the caller is responsible
+    /// for making sure it is aligned to a multiple of `ALIGN`.
+    #[inline(always)]
+    pub const unsafe fn new_unchecked(addr: PhysAddr) -> Self {
+        let _ = Self::ALIGN_MASK;
+
+        Self(addr)
+    }
+
+    /// Gets the wrapped physical address.
+    #[inline(always)]
+    pub const fn get(self) -> PhysAddr {
+        self.0
+    }
+
+    /// Gets the referenced memory address as [`usize`].
+    #[inline(always)]
+    pub fn addr(self) -> usize {
+        self.0.addr()
+    }
+}
+
+impl<const ALIGN: usize> From<Aligned<ALIGN>> for PhysAddr {
+    #[inline(always)]
+    fn from(addr: Aligned<ALIGN>) -> Self {
+        addr.0
+    }
+}
+
+impl<const ALIGN: usize> TryFrom<PhysAddr> for Aligned<ALIGN> {
+    type Error = MisalignedAddress;
+
+    #[inline(always)]
+    fn try_from(addr: PhysAddr) -> Result<Self, Self::Error> {
+        Self::try_new(addr)
+    }
 }
 
 impl_fmt_traits!(for PhysAddr);
 impl_fmt_traits!(for VirtAddr);
+
+// Stepping through a range of addresses re-validates every
+// produced address, so iteration stops rather than yielding
+// an address which breaks the invariants of the type.
+//
+// Combined with `Iterator::step_by`, this enables iteration
+// in page-sized strides, e.g. `(start..end).step_by(0x1000)`.
+impl Step for PhysAddr {
+    #[inline]
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        end.addr().checked_sub(start.addr())
+    }
+
+    #[inline]
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        let addr = start.addr().checked_add(count)?;
+        start.map_addr(|_| addr).ok()
+    }
+
+    #[inline]
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        let addr = start.addr().checked_sub(count)?;
+        start.map_addr(|_| addr).ok()
+    }
+}
+
+// Stepping never leaves the half of the address space the start
+// address is in, see the documentation of `VirtAddr`.
+
+impl Step for VirtAddr {
+    #[inline]
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        end.addr()
+            .checked_sub(start.addr())
+            .filter(|_| start.is_same_half(end.addr()))
+    }
+
+    #[inline]
+    fn forward_checked(start: Self, count: usize) -> Option<Self> {
+        let addr = start
+            .addr()
+            .checked_add(count)
+            .filter(|&addr| start.is_same_half(addr))?;
+        Some(Self(start.0.with_addr(addr)))
+    }
+
+    #[inline]
+    fn backward_checked(start: Self, count: usize) -> Option<Self> {
+        let addr = start
+            .addr()
+            .checked_sub(count)
+            .filter(|&addr| start.is_same_half(addr))?;
+        Some(Self(start.0.with_addr(addr)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOW_END: usize = 0x0000_FFFF_FFFF_F000;
+    const HIGH_START: usize = 0xFFFF_0000_0000_0000;
+
+    fn virt(addr: usize) -> VirtAddr {
+        VirtAddr::new(addr as *mut ())
+    }
+
+    fn phys(addr: usize) -> PhysAddr {
+        PhysAddr::new(addr as *mut ())
+    }
+
+    #[test]
+    fn step_within_half() {
+        assert_eq!(Step::forward_checked(virt(0), LOW_END), Some(virt(LOW_END)));
+        assert_eq!(
+            Step::backward_checked(virt(usize::MAX), 0xFFF),
+            Some(virt(usize::MAX - 0xFFF))
+        );
+        assert_eq!(
+            Step::steps_between(&virt(HIGH_START), &virt(usize::MAX)),
+            Some(usize::MAX - HIGH_START)
+        );
+    }
+
+    #[test]
+    fn step_into_hole() {
+        assert_eq!(Step::forward_checked(virt(LOW_END), 0x1000), None);
+        assert_eq!(Step::backward_checked(virt(HIGH_START), 1), None);
+    }
+
+    #[test]
+    fn step_across_hole() {
+        let count = HIGH_START - LOW_END;
+        assert_eq!(Step::forward_checked(virt(LOW_END), count), None);
+        assert_eq!(Step::backward_checked(virt(HIGH_START), count), None);
+        assert_eq!(Step::steps_between(&virt(LOW_END), &virt(HIGH_START)), None);
+
+        // Iteration stops at the end of the lower half.
+        let last = (virt(LOW_END)..virt(HIGH_START)).step_by(0x1000).last();
+        assert_eq!(last, Some(virt(LOW_END)));
+    }
+
+    #[test]
+    fn aligned_accepts_aligned() {
+        let aligned = Aligned::<0x1000>::try_new(phys(0x8000_0000)).unwrap();
+        assert_eq!(aligned.get(), phys(0x8000_0000));
+        assert_eq!(aligned.addr(), 0x8000_0000);
+
+        let aligned: Aligned<0x1000> = phys(0).try_into().unwrap();
+        assert_eq!(PhysAddr::from(aligned), phys(0));
+
+        // Any address satisfies an alignment of one.
+        assert!(Aligned::<1>::try_new(phys(0x1234_5677)).is_ok());
+    }
+
+    #[test]
+    fn aligned_rejects_misaligned() {
+        let err = Aligned::<0x1000>::try_new(phys(0x8000_0800)).unwrap_err();
+        assert_eq!(err.0, 0x8000_0800);
+
+        let res: Result<Aligned<0x1000>, _> = phys(0xFFF).try_into();
+        assert_eq!(res.unwrap_err().0, 0xFFF);
+
+        // A smaller alignment does not satisfy a larger one.
+        assert!(Aligned::<0x20_0000>::try_new(phys(0x1000)).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Implementation details of [`crate::cpu`].
+
+use utils::bits::bitmask;
+
+/// The implementer code of Arm Limited in `MIDR_EL1`.
+pub const IMPLEMENTER_ARM: u8 = 0x41;
+
+/// The primary part number of the Cortex-A53 in `MIDR_EL1`.
+pub const PART_CORTEX_A53: u16 = 0xD03;
+/// The primary part number of the Cortex-A57 in `MIDR_EL1`.
+pub const PART_CORTEX_A57: u16 = 0xD07;
+
+/// Identification of a CPU core, decoded from `MIDR_EL1`.
+///
+/// On big.LITTLE systems, cores of different types are combined
+/// in a single system, so the register must be read on the core
+/// in question.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuInfo {
+    midr: u64,
+}
+
+impl CpuInfo {
+    /// Decodes the identification from a raw `MIDR_EL1` value.
+    #[inline(always)]
+    pub const fn from_midr(midr: u64) -> Self {
+        Self { midr }
+    }
+
+    // Extracts the field of `len` bits at bit `start`.
+    #[inline(always)]
+    const fn field(&self, start: u32, len: u32) -> u64 {
+        (self.midr >> start) & bitmask(0, len) as u64
+    }
+
+    /// Gets the implementer code of the core, e.g. [`IMPLEMENTER_ARM`].
+    #[inline(always)]
+    pub const fn implementer(&self) -> u8 {
+        self.field(24, 8) as u8
+    }
+
+    /// Gets the variant number of the core, i.e. the `r` in `rNpN`.
+    #[inline(always)]
+    pub const fn variant(&self) -> u8 {
+        self.field(20, 4) as u8
+    }
+
+    /// Gets the primary part number of the core.
+    ///
+    /// This is only meaningful together with the [implementer].
+    ///
+    /// [implementer]: CpuInfo::implementer
+    #[inline(always)]
+    pub const fn part(&self) -> u16 {
+        self.field(4, 12) as u16
+    }
+
+    /// Gets the revision number of the core, i.e. the `p` in `rNpN`.
+    #[inline(always)]
+    pub const fn revision(&self) -> u8 {
+        self.field(0, 4) as u8
+    }
+
+    /// Checks whether the core is an Arm Cortex-A53.
+    #[inline(always)]
+    pub const fn is_a53(&self) -> bool {
+        self.implementer() == IMPLEMENTER_ARM && self.part() == PART_CORTEX_A53
+    }
+
+    /// Checks whether the core is an Arm Cortex-A57.
+    #[inline(always)]
+    pub const fn is_a57(&self) -> bool {
+        self.implementer() == IMPLEMENTER_ARM && self.part() == PART_CORTEX_A57
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cortex-A57 r1p3, as found in the Tegra X1.
+    const MIDR_A57_R1P3: u64 = 0x411F_D073;
+    // Cortex-A53 r0p4.
+    const MIDR_A53_R0P4: u64 = 0x410F_D034;
+
+    #[test]
+    fn decode_fields() {
+        let cpu = CpuInfo::from_midr(MIDR_A57_R1P3);
+        assert_eq!(cpu.implementer(), IMPLEMENTER_ARM);
+        assert_eq!(cpu.variant(), 1);
+        assert_eq!(cpu.part(), PART_CORTEX_A57);
+        assert_eq!(cpu.revision(), 3);
+
+        let cpu = CpuInfo::from_midr(MIDR_A53_R0P4);
+        assert_eq!(cpu.implementer(), IMPLEMENTER_ARM);
+        assert_eq!(cpu.variant(), 0);
+        assert_eq!(cpu.part(), PART_CORTEX_A53);
+        assert_eq!(cpu.revision(), 4);
+    }
+
+    #[test]
+    fn ignore_upper_bits() {
+        // Only the low 32 bits of `MIDR_EL1` are defined.
+        let cpu = CpuInfo::from_midr(0xFFFF_FFFF_0000_0000 | MIDR_A57_R1P3);
+        assert_eq!(cpu.implementer(), IMPLEMENTER_ARM);
+        assert_eq!(cpu.part(), PART_CORTEX_A57);
+        assert!(cpu.is_a57());
+    }
+
+    #[test]
+    fn identify_cores() {
+        assert!(CpuInfo::from_midr(MIDR_A57_R1P3).is_a57());
+        assert!(!CpuInfo::from_midr(MIDR_A57_R1P3).is_a53());
+        assert!(CpuInfo::from_midr(MIDR_A53_R0P4).is_a53());
+        assert!(!CpuInfo::from_midr(MIDR_A53_R0P4).is_a57());
+
+        // Same part number, but a different implementer.
+        let other = (MIDR_A57_R1P3 & !(0xFF << 24)) | (0x4E << 24);
+        assert_eq!(CpuInfo::from_midr(other).part(), PART_CORTEX_A57);
+        assert!(!CpuInfo::from_midr(other).is_a57());
+    }
+}
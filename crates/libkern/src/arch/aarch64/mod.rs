@@ -1,4 +1,6 @@
 //! AArch64-specific implementation details.
 
 pub mod addr;
+pub mod cpu;
 pub mod init;
+pub mod sync;
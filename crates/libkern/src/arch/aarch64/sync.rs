@@ -0,0 +1,51 @@
+//! AArch64-specific implementation details of [`crate::sync`].
+
+#[cfg(target_arch = "aarch64")]
+use core::arch::asm;
+#[cfg(not(target_arch = "aarch64"))]
+use core::sync::atomic::{fence, Ordering};
+
+/// Puts the current core into a low-power state until an event
+/// is signaled.
+///
+/// Wake-ups may happen spuriously, so this must be used in a loop
+/// which re-checks the awaited condition.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn wait_for_event() {
+    // SAFETY: `wfe` is merely a hint and has no side effects.
+    unsafe { asm!("wfe", options(nomem, nostack, preserves_flags)) }
+}
+
+/// Puts the current core into a low-power state until an event
+/// is signaled.
+///
+/// Wake-ups may happen spuriously, so this must be used in a loop
+/// which re-checks the awaited condition.
+#[cfg(not(target_arch = "aarch64"))]
+#[inline(always)]
+pub fn wait_for_event() {
+    // Unit tests on the host give other threads a chance to run.
+    std::thread::yield_now();
+}
+
+/// Signals an event to all cores waiting in [`wait_for_event`].
+///
+/// All prior stores are made observable before the event is
+/// signaled, so woken up cores will see them.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub fn send_event() {
+    // SAFETY: Barriers and `sev` do not break any memory invariants.
+    unsafe { asm!("dsb ishst", "sev", options(nostack, preserves_flags)) }
+}
+
+/// Signals an event to all cores waiting in [`wait_for_event`].
+///
+/// All prior stores are made observable before the event is
+/// signaled, so woken up cores will see them.
+#[cfg(not(target_arch = "aarch64"))]
+#[inline(always)]
+pub fn send_event() {
+    fence(Ordering::Release);
+}
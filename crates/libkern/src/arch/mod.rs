@@ -1,7 +1,9 @@
 //! Architecture-specific implementation details.
 
 cfg_if::cfg_if! {
-    if #[cfg(target_arch = "aarch64")] {
+    // Unit tests run on the host, which gets the AArch64 code with
+    // portable stand-ins for its special instructions.
+    if #[cfg(any(target_arch = "aarch64", test))] {
         mod aarch64;
         pub use self::aarch64::*;
     } else {
@@ -0,0 +1,6 @@
+//! Identification of the CPU cores the Kernel runs on.
+//!
+//! The identification registers are decoded here, while reading
+//! them is left to the Kernel, which has direct register access.
+
+pub use crate::arch::cpu::*;
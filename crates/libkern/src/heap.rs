@@ -0,0 +1,183 @@
+//! Heap allocators for the Kernel.
+//!
+//! These implement [`GlobalAlloc`] and are meant to be installed
+//! as the `#[global_allocator]` to make the `alloc` crate usable.
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    ptr,
+};
+
+use crate::sync::SpinMutex;
+
+struct BumpState {
+    start: *mut u8,
+    size: usize,
+    offset: usize,
+    allocations: usize,
+}
+
+// SAFETY: The state only describes the memory region which was
+// handed to the allocator, so it is not tied to a specific core.
+unsafe impl Send for BumpState {}
+
+/// A simple allocator which hands out memory from a fixed region
+/// in a linear fashion.
+///
+/// Freeing memory is a no-op, except that the whole region is
+/// reclaimed once all outstanding allocations have been freed.
+/// This makes it well-suited as the heap of early Kernel stages
+/// which allocate a few long-lived objects.
+///
+/// The allocator must be given its memory region with
+/// [`BumpAllocator::init`] before it can serve allocations.
+pub struct BumpAllocator {
+    state: SpinMutex<BumpState>,
+}
+
+impl BumpAllocator {
+    /// Creates a new allocator without any memory to serve
+    /// allocations from.
+    pub const fn new() -> Self {
+        Self {
+            state: SpinMutex::new(BumpState {
+                start: ptr::null_mut(),
+                size: 0,
+                offset: 0,
+                allocations: 0,
+            }),
+        }
+    }
+
+    /// Hands the memory region of `size` bytes at `start` to the
+    /// allocator, discarding any previous region.
+    ///
+    /// # Safety
+    ///
+    /// - `start` must be valid for reads and writes of `size` bytes
+    ///   for as long as the allocator is in use.
+    /// - The memory must not be accessed through other means while
+    ///   the allocator is in use.
+    /// - No allocations from a previous region may be outstanding.
+    pub unsafe fn init(&self, start: *mut u8, size: usize) {
+        *self.state.lock() = BumpState {
+            start,
+            size,
+            offset: 0,
+            allocations: 0,
+        };
+    }
+
+    /// Gets the number of bytes which are still available for
+    /// allocations, disregarding alignment requirements.
+    pub fn remaining(&self) -> usize {
+        let state = self.state.lock();
+        state.size - state.offset
+    }
+}
+
+impl Default for BumpAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let mut state = self.state.lock();
+
+        // Compute the padding to the next suitably aligned address.
+        let addr = state.start.addr() + state.offset;
+        let padding = addr.wrapping_neg() & (layout.align() - 1);
+
+        let end = state
+            .offset
+            .checked_add(padding)
+            .and_then(|offset| offset.checked_add(layout.size()));
+        match end {
+            Some(end) if !state.start.is_null() && end <= state.size => {
+                let ptr = state.start.add(state.offset + padding);
+                state.offset = end;
+                state.allocations += 1;
+
+                ptr
+            }
+            _ => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        let mut state = self.state.lock();
+
+        // Once nothing refers to the region anymore, it can be
+        // reused from the start.
+        state.allocations -= 1;
+        if state.allocations == 0 {
+            state.offset = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 0x100;
+
+    // Hands `SIZE` bytes of `heap` to a new allocator. The region
+    // starts 16-byte aligned, so that the padding is predictable.
+    fn allocator(heap: &mut [u8]) -> (BumpAllocator, usize) {
+        let offset = heap.as_ptr().align_offset(16);
+        let start = &mut heap[offset..];
+        assert!(start.len() >= SIZE);
+
+        let allocator = BumpAllocator::new();
+        unsafe { allocator.init(start.as_mut_ptr(), SIZE) };
+        (allocator, start.as_ptr().addr())
+    }
+
+    #[test]
+    fn align_allocations() {
+        let mut heap = vec![0u8; SIZE + 16];
+        let (allocator, start) = allocator(&mut heap);
+
+        unsafe {
+            let byte = allocator.alloc(Layout::new::<u8>());
+            assert_eq!(byte.addr(), start);
+
+            // Padding is inserted to reach the requested alignment.
+            let word = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(word.addr(), start + 8);
+            let block = allocator.alloc(Layout::from_size_align(1, 16).unwrap());
+            assert_eq!(block.addr(), start + 16);
+        }
+        assert_eq!(allocator.remaining(), SIZE - 17);
+    }
+
+    #[test]
+    fn out_of_memory() {
+        let mut heap = vec![0u8; SIZE + 16];
+        let (allocator, _) = allocator(&mut heap);
+
+        unsafe {
+            let layout = Layout::from_size_align(SIZE + 1, 1).unwrap();
+            assert!(allocator.alloc(layout).is_null());
+
+            // The padding for alignment counts towards the size.
+            assert!(!allocator.alloc(Layout::new::<u8>()).is_null());
+            let layout = Layout::from_size_align(SIZE - 7, 8).unwrap();
+            assert!(allocator.alloc(layout).is_null());
+
+            // Failed allocations leave the region untouched.
+            let layout = Layout::from_size_align(SIZE - 1, 1).unwrap();
+            assert!(!allocator.alloc(layout).is_null());
+            assert_eq!(allocator.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn uninitialized() {
+        let allocator = BumpAllocator::new();
+        assert!(unsafe { allocator.alloc(Layout::new::<u8>()) }.is_null());
+    }
+}
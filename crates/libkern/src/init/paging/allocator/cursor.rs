@@ -147,7 +147,7 @@ impl Cursor {
         let current_end_addr = current_start_addr + self.current().size;
 
         // Attempt to merge the holes together, if possible.
-        if hole_start_addr < hole_end_addr {
+        if hole_start_addr < current_start_addr {
             if hole_end_addr < current_start_addr {
                 *hole.as_mut() = Hole {
                     next: Some(self.current),
@@ -163,6 +163,17 @@ impl Cursor {
             }
         } else if current_end_addr == hole_start_addr {
             self.current_mut().size += size;
+
+            // The hole may close the gap to the next one as well.
+            if let Some(next) = self.current().next {
+                if next.addr().get() == hole_end_addr {
+                    let next = next.as_ref();
+                    *self.current_mut() = Hole {
+                        next: next.next,
+                        size: self.current().size + next.size,
+                    };
+                }
+            }
             return Ok((self, false));
         }
 
@@ -1,6 +1,6 @@
 use core::{alloc::Layout, ptr::NonNull};
 
-use super::cursor::Cursor;
+use super::{cursor::Cursor, FreeListStats};
 
 // A hole in the allocator's memory region that marks
 // a chunk of free, allocatable storage of `size` bytes.
@@ -25,6 +25,24 @@ impl HoleList {
         Cursor::new(self)
     }
 
+    pub fn stats(&self) -> FreeListStats {
+        let mut stats = FreeListStats::default();
+
+        let mut current = self.head;
+        while let Some(hole) = current {
+            // SAFETY: The list only links to valid holes.
+            let hole = unsafe { hole.as_ref() };
+
+            stats.nodes += 1;
+            stats.free_bytes += hole.size;
+            stats.largest_block = stats.largest_block.max(hole.size);
+
+            current = hole.next;
+        }
+
+        stats
+    }
+
     pub fn is_allocatable(&mut self, layout: Layout) -> bool {
         #[inline]
         fn is_allocatable_impl(list: &mut HoleList, layout: Layout) -> Result<(), ()> {
@@ -88,3 +106,51 @@ impl HoleList {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Backing storage for the holes, aligned for `Hole`.
+    struct Memory([u64; 32]);
+
+    impl Memory {
+        fn block(&mut self, offset: usize) -> NonNull<Hole> {
+            let base = self.0.as_mut_ptr().cast::<u8>();
+            NonNull::new(base.wrapping_add(offset).cast()).unwrap()
+        }
+    }
+
+    fn stats(nodes: usize, free_bytes: usize, largest_block: usize) -> FreeListStats {
+        FreeListStats {
+            nodes,
+            free_bytes,
+            largest_block,
+        }
+    }
+
+    #[test]
+    fn stats_and_coalescing() {
+        let mut memory = Memory([0; 32]);
+        let mut list = HoleList::empty();
+        assert_eq!(list.stats(), stats(0, 0, 0));
+
+        unsafe {
+            list.free(memory.block(0x00), 0x20);
+            list.free(memory.block(0x40), 0x20);
+            list.free(memory.block(0xA0), 0x40);
+        }
+        assert_eq!(list.stats(), stats(3, 0x80, 0x40));
+
+        // Closing the gap merges both neighbors into one block.
+        unsafe { list.free(memory.block(0x20), 0x20) };
+        assert_eq!(list.stats(), stats(2, 0xA0, 0x60));
+
+        // Freeing directly in front of a hole extends it.
+        unsafe { list.free(memory.block(0x80), 0x20) };
+        assert_eq!(list.stats(), stats(2, 0xC0, 0x60));
+
+        unsafe { list.free(memory.block(0x60), 0x20) };
+        assert_eq!(list.stats(), stats(1, 0xE0, 0xE0));
+    }
+}
@@ -4,13 +4,19 @@ use core::{
     ptr::{self, NonNull},
 };
 
-use crate::{addr::PhysAddr, BUILD_CONFIG};
+use utils::mem::bit_size_of;
+
+use crate::{addr::PhysAddr, memory::RegionMap, BUILD_CONFIG};
 
 mod cursor;
 
 mod hole;
 use self::hole::{Hole, HoleList};
 
+// The number of bytes the allocator grows its memory by when no
+// hole can satisfy an allocation.
+const UNIT_SIZE: usize = bit_size_of::<u64>() * BUILD_CONFIG.page_size;
+
 /// The state managed by [`InitialPageAllocator`].
 #[repr(C)]
 pub struct AllocatorState {
@@ -19,6 +25,21 @@ pub struct AllocatorState {
     list: HoleList,
 }
 
+/// Statistics about the free memory of an [`InitialPageAllocator`].
+///
+/// Comparing the largest block to the total amount of free memory
+/// tells whether failing allocations are caused by exhaustion or
+/// by fragmentation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FreeListStats {
+    /// The number of disjoint blocks of free memory.
+    pub nodes: usize,
+    /// The total number of free bytes.
+    pub free_bytes: usize,
+    /// The size of the largest contiguous block of free memory.
+    pub largest_block: usize,
+}
+
 /// A page allocator to be used for setup during initial Kernel
 /// bootstrap.
 ///
@@ -70,6 +91,13 @@ impl InitialPageAllocator {
         &self.state
     }
 
+    /// Collects statistics about the allocator's free memory.
+    ///
+    /// This walks the entire free list, so it is not cheap.
+    pub fn stats(&self) -> FreeListStats {
+        self.state.list.stats()
+    }
+
     /// Consumes the allocator and returns its state.
     #[inline(always)]
     pub fn into_state(self) -> AllocatorState {
@@ -108,6 +136,36 @@ impl InitialPageAllocator {
         self.state.start_address = start;
     }
 
+    /// Initializes an empty allocator to the start of the
+    /// largest free region in the given [`RegionMap`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when the map holds no free memory or when the largest
+    /// free region cannot fit [`Self::MIN_SIZE`] bytes after aligning
+    /// its start, in addition to the conditions of
+    /// [`InitialPageAllocator::init`].
+    ///
+    /// # Safety
+    ///
+    /// The free regions in `regions` must describe unused
+    /// physical memory. The remaining requirements are the
+    /// same as for [`InitialPageAllocator::init`].
+    pub unsafe fn init_from_regions<const N: usize>(&mut self, regions: &RegionMap<N>) {
+        let region = regions
+            .largest_free_region()
+            .expect("No free memory to allocate from");
+
+        let start = region
+            .base
+            .align_up(Self::MIN_SIZE)
+            .ok()
+            .filter(|start| start.addr() + Self::MIN_SIZE <= region.end())
+            .expect("Largest free region is too small to allocate from");
+
+        self.init(start)
+    }
+
     fn align_layout(layout: Layout) -> Layout {
         Layout::from_size_align(
             layout.size().max(Self::MIN_SIZE),
@@ -141,7 +199,7 @@ impl InitialPageAllocator {
             unsafe {
                 self.state.list.free(
                     NonNull::new_unchecked(self.state.end_address.as_mut_ptr()),
-                    u64::BITS as usize * BUILD_CONFIG.page_size,
+                    UNIT_SIZE,
                 );
 
                 self.state.end_address = self
@@ -0,0 +1,385 @@
+//! Description of the Kernel's address space layout.
+//!
+//! The Kernel encodes the offsets of its segments in its metadata
+//! and the Kernel Loader maps them accordingly. Both sides should
+//! use [`AddressSpaceLayout`] to reason about them so that their
+//! view of the layout cannot drift apart.
+
+use core::{
+    mem::{align_of, size_of, MaybeUninit},
+    ptr::addr_of,
+};
+
+use utils::align::is_aligned;
+
+/// The segments of the Kernel binary, in the order they appear in
+/// memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum SegmentKind {
+    /// The `.text` segment with executable code.
+    Text,
+    /// The `.rodata` segment with read-only data.
+    Rodata,
+    /// The `.data` segment with initialized, writable data.
+    Data,
+    /// The `.bss` segment with zero-initialized, writable data.
+    Bss,
+}
+
+impl SegmentKind {
+    /// Gets the name of the segment's section in the binary.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => ".text",
+            Self::Rodata => ".rodata",
+            Self::Data => ".data",
+            Self::Bss => ".bss",
+        }
+    }
+
+    /// Gets the access permissions the segment is mapped with.
+    pub const fn permissions(self) -> Permissions {
+        match self {
+            Self::Text => Permissions::ReadExecute,
+            Self::Rodata => Permissions::Read,
+            Self::Data | Self::Bss => Permissions::ReadWrite,
+        }
+    }
+}
+
+/// The memory access permissions of a [`Segment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Permissions {
+    /// Memory may be read.
+    Read = 0b100,
+    /// Memory may be read and written.
+    ReadWrite = 0b110,
+    /// Memory may be read and executed.
+    ReadExecute = 0b101,
+}
+
+/// A segment of the Kernel binary.
+///
+/// `start` and `end` are offsets from the Kernel base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct Segment {
+    /// Which segment this is.
+    pub kind: SegmentKind,
+    /// The permissions to map the segment with.
+    pub permissions: Permissions,
+    /// The start offset of the segment, inclusive.
+    pub start: u32,
+    /// The end offset of the segment, exclusive.
+    pub end: u32,
+}
+
+impl Segment {
+    /// Creates a new segment of the given kind, with the default
+    /// permissions for it.
+    #[inline(always)]
+    pub const fn new(kind: SegmentKind, start: u32, end: u32) -> Self {
+        Self {
+            kind,
+            permissions: kind.permissions(),
+            start,
+            end,
+        }
+    }
+
+    /// Gets the size of the segment in bytes.
+    #[inline(always)]
+    pub const fn size(&self) -> u32 {
+        self.end - self.start
+    }
+}
+
+/// Errors produced by [`AddressSpaceLayout::validate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// A segment ends before it starts.
+    Inverted(SegmentKind),
+    /// A segment starts before its predecessor ends.
+    Unordered(SegmentKind),
+    /// A segment boundary shared with a segment of different
+    /// permissions is not page-aligned.
+    Misaligned(SegmentKind),
+    /// A segment reaches past the end of the Kernel.
+    OutOfBounds(SegmentKind),
+}
+
+/// The layout of the Kernel's segments in its address space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct AddressSpaceLayout {
+    /// The segments of the Kernel, in the order of [`SegmentKind`].
+    pub segments: [Segment; 4],
+    /// The end offset of the Kernel, including `.bss`.
+    pub kernel_end: u32,
+    /// The start offset of the Kernel's `_DYNAMIC` array.
+    pub dynamic_start: u32,
+}
+
+impl AddressSpaceLayout {
+    /// Gets the segment of the given kind.
+    #[inline(always)]
+    pub const fn segment(&self, kind: SegmentKind) -> &Segment {
+        &self.segments[kind as usize]
+    }
+
+    /// Validates that the segments are ordered, non-overlapping and
+    /// within the bounds of the Kernel.
+    ///
+    /// Neighboring segments of different permissions must be split
+    /// at a multiple of `page_size`, so that they can be mapped
+    /// separately. Segments with equal permissions, i.e. `.data`
+    /// and `.bss`, may share a page.
+    pub fn validate(&self, page_size: usize) -> Result<(), LayoutError> {
+        let aligned = |offset: u32| is_aligned(offset as usize, page_size);
+
+        let mut previous: Option<&Segment> = None;
+        for segment in &self.segments {
+            if segment.start > segment.end {
+                return Err(LayoutError::Inverted(segment.kind));
+            }
+            if segment.end > self.kernel_end {
+                return Err(LayoutError::OutOfBounds(segment.kind));
+            }
+
+            match previous {
+                Some(prev) if prev.end > segment.start => {
+                    return Err(LayoutError::Unordered(segment.kind));
+                }
+                Some(prev) if prev.permissions == segment.permissions => {}
+                Some(prev) if !aligned(prev.end) => {
+                    return Err(LayoutError::Misaligned(prev.kind));
+                }
+                _ if !aligned(segment.start) => {
+                    return Err(LayoutError::Misaligned(segment.kind));
+                }
+                _ => {}
+            }
+
+            previous = Some(segment);
+        }
+
+        // The last segment's end is the end of the mapped image.
+        match previous {
+            Some(last) if !aligned(last.end) => Err(LayoutError::Misaligned(last.kind)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The raw layout of the Kernel's sections, as encoded in its
+/// metadata.
+///
+/// This is the single definition for the Kernel and the Kernel
+/// Loader, which receives a pointer to it. It must be kept in sync
+/// with `__saturnus_kernel_layout` in the Kernel's startup code and
+/// with `KernelLayout` in the `kernel-image` crate, which cannot
+/// depend on this crate since it runs on the build host.
+///
+/// All fields are offsets from the Kernel base.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct KernelLayout {
+    /// The start offset of the Kernel's `.text` section.
+    pub text_start: u32,
+    /// The end offset of the Kernel's `.text` section.
+    pub text_end: u32,
+    /// The start offset of the Kernel's `.rodata` section.
+    pub rodata_start: u32,
+    /// The end offset of the Kernel's `.rodata` section.
+    pub rodata_end: u32,
+    /// The start offset of the Kernel's `.data` section.
+    pub data_start: u32,
+    /// The end offset of the Kernel's `.data` section.
+    pub data_end: u32,
+    /// The start offset of the Kernel's `.bss` section.
+    pub bss_start: u32,
+    /// The end offset of the Kernel's `.bss` section.
+    pub bss_end: u32,
+    /// The end offset of the Kernel binary.
+    pub kernel_end: u32,
+    /// The start offset of the Kernel's `_DYNAMIC` array.
+    pub dynamic_start: u32,
+}
+
+// The layout is embedded into the Kernel's metadata without any
+// padding; the `kernel-image` crate asserts the same size and
+// checks the same field offsets in its tests.
+const _: () = assert!(size_of::<KernelLayout>() == 0x28);
+const _: () = assert!(align_of::<KernelLayout>() == 4);
+
+macro_rules! assert_field_offset {
+    ($($field:ident => $offset:expr),* $(,)?) => {
+        $(
+            const _: () = {
+                let layout = MaybeUninit::<KernelLayout>::uninit();
+                let base = layout.as_ptr();
+                // SAFETY: No reference to the uninitialized value is formed.
+                let offset = unsafe {
+                    addr_of!((*base).$field)
+                        .cast::<u8>()
+                        .offset_from(base.cast::<u8>())
+                };
+                assert!(offset == $offset);
+            };
+        )*
+    };
+}
+
+assert_field_offset! {
+    text_start => 0x00,
+    text_end => 0x04,
+    rodata_start => 0x08,
+    rodata_end => 0x0C,
+    data_start => 0x10,
+    data_end => 0x14,
+    bss_start => 0x18,
+    bss_end => 0x1C,
+    kernel_end => 0x20,
+    dynamic_start => 0x24,
+}
+
+impl KernelLayout {
+    /// Converts the raw section offsets into an [`AddressSpaceLayout`].
+    pub const fn address_space_layout(&self) -> AddressSpaceLayout {
+        AddressSpaceLayout {
+            segments: [
+                Segment::new(SegmentKind::Text, self.text_start, self.text_end),
+                Segment::new(SegmentKind::Rodata, self.rodata_start, self.rodata_end),
+                Segment::new(SegmentKind::Data, self.data_start, self.data_end),
+                Segment::new(SegmentKind::Bss, self.bss_start, self.bss_end),
+            ],
+            kernel_end: self.kernel_end,
+            dynamic_start: self.dynamic_start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: usize = 0x1000;
+
+    // A valid layout where `.data` and `.bss` share a page.
+    fn kernel_layout() -> KernelLayout {
+        KernelLayout {
+            text_start: 0x0000,
+            text_end: 0x1000,
+            rodata_start: 0x1000,
+            rodata_end: 0x2000,
+            data_start: 0x2000,
+            data_end: 0x2800,
+            bss_start: 0x2800,
+            bss_end: 0x3000,
+            kernel_end: 0x3000,
+            dynamic_start: 0x1800,
+        }
+    }
+
+    fn validate(layout: KernelLayout) -> Result<(), LayoutError> {
+        layout.address_space_layout().validate(PAGE_SIZE)
+    }
+
+    #[test]
+    fn address_space_layout() {
+        let layout = kernel_layout().address_space_layout();
+
+        let text = layout.segment(SegmentKind::Text);
+        assert_eq!((text.start, text.end), (0x0000, 0x1000));
+        assert_eq!(text.permissions, Permissions::ReadExecute);
+        let rodata = layout.segment(SegmentKind::Rodata);
+        assert_eq!((rodata.start, rodata.end), (0x1000, 0x2000));
+        assert_eq!(rodata.permissions, Permissions::Read);
+        let data = layout.segment(SegmentKind::Data);
+        assert_eq!((data.start, data.end), (0x2000, 0x2800));
+        assert_eq!(data.permissions, Permissions::ReadWrite);
+        let bss = layout.segment(SegmentKind::Bss);
+        assert_eq!((bss.start, bss.end), (0x2800, 0x3000));
+        assert_eq!(bss.permissions, Permissions::ReadWrite);
+
+        assert_eq!(layout.kernel_end, 0x3000);
+        assert_eq!(layout.dynamic_start, 0x1800);
+        assert!(layout.segments.iter().all(|s| layout.segment(s.kind) == s));
+    }
+
+    #[test]
+    fn shared_data_bss_page() {
+        assert_eq!(validate(kernel_layout()), Ok(()));
+    }
+
+    #[test]
+    fn inverted() {
+        let layout = KernelLayout {
+            rodata_start: 0x2000,
+            rodata_end: 0x1000,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::Inverted(SegmentKind::Rodata))
+        );
+    }
+
+    #[test]
+    fn unordered() {
+        let layout = KernelLayout {
+            data_start: 0x1000,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::Unordered(SegmentKind::Data))
+        );
+    }
+
+    #[test]
+    fn misaligned_permission_boundary() {
+        let layout = KernelLayout {
+            text_end: 0x0800,
+            rodata_start: 0x0800,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::Misaligned(SegmentKind::Text))
+        );
+
+        let layout = KernelLayout {
+            rodata_end: 0x1800,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::Misaligned(SegmentKind::Rodata))
+        );
+
+        let layout = KernelLayout {
+            bss_end: 0x2C00,
+            kernel_end: 0x2C00,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::Misaligned(SegmentKind::Bss))
+        );
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let layout = KernelLayout {
+            kernel_end: 0x2000,
+            ..kernel_layout()
+        };
+        assert_eq!(
+            validate(layout),
+            Err(LayoutError::OutOfBounds(SegmentKind::Data))
+        );
+    }
+}
@@ -1,17 +1,24 @@
 //!
 
-#![feature(ptr_as_uninit, strict_provenance)]
-#![no_std]
+#![feature(const_ptr_offset_from, ptr_as_uninit, step_trait, strict_provenance)]
+#![cfg_attr(not(test), no_std)]
 
 pub use config::Config;
 
 mod arch;
 
 pub mod addr;
+pub mod cpu;
+pub mod heap;
 pub mod init;
+pub mod layout;
+pub mod memory;
+pub mod sync;
 
 /// The build configuration for the currently configured target.
 pub const BUILD_CONFIG: Config = match config::CURRENT_BUILD {
     Some(config) => config,
+    // Unit tests run on the host, which has no configuration.
+    None if cfg!(test) => config::AARCH64_QEMU,
     None => panic!("Building libkern with unsupported target configuration"),
 };
@@ -0,0 +1,7 @@
+//! Management of physical memory for the Kernel.
+//!
+//! The memory layout of a board is described in terms of
+//! [`MemoryRegion`]s which are tracked in a [`RegionMap`].
+
+mod region;
+pub use self::region::*;
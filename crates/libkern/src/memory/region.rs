@@ -0,0 +1,374 @@
+use core::ptr;
+
+use crate::addr::PhysAddr;
+
+/// The kind of memory in a [`MemoryRegion`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegionKind {
+    /// DRAM which is free for use by the Kernel.
+    Free,
+    /// Memory carved out for use by hardware or firmware.
+    Carveout,
+    /// Memory reserved for any other purpose, e.g. to hold
+    /// the Kernel image itself.
+    Reserved,
+}
+
+/// A contiguous region of physical memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// The physical start address of the region.
+    pub base: PhysAddr,
+    /// The size of the region in bytes.
+    pub size: usize,
+    /// What the memory in the region is used for.
+    pub kind: RegionKind,
+}
+
+impl MemoryRegion {
+    const EMPTY: Self = Self {
+        base: unsafe { PhysAddr::new_unchecked(ptr::null_mut::<()>()) },
+        size: 0,
+        kind: RegionKind::Free,
+    };
+
+    /// Creates a new memory region of the given properties.
+    #[inline(always)]
+    pub const fn new(base: PhysAddr, size: usize, kind: RegionKind) -> Self {
+        Self { base, size, kind }
+    }
+
+    /// Gets the physical end address of the region, exclusive.
+    #[inline(always)]
+    pub fn end(&self) -> usize {
+        self.base.addr() + self.size
+    }
+
+    #[inline(always)]
+    fn with_bounds(&self, start: usize, end: usize, kind: RegionKind) -> Self {
+        Self {
+            base: self.base.map_addr(|_| start).unwrap(),
+            size: end - start,
+            kind,
+        }
+    }
+}
+
+/// Errors produced when updating a [`RegionMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegionError {
+    /// The map has no capacity left for the resulting regions.
+    CapacityExceeded,
+    /// A region is empty or reaches past the address space.
+    InvalidRegion,
+    /// A new region overlaps with an existing one.
+    Overlap,
+    /// A range to reserve is not fully covered by free memory.
+    NotFree,
+}
+
+/// A fixed-capacity map of up to `N` physical [`MemoryRegion`]s.
+///
+/// Regions are kept sorted by base address and never overlap.
+/// Adjacent regions of the same kind are merged automatically.
+///
+/// The map is meant to be seeded with the memory layout of the
+/// board, after which ranges in use can be carved out of free
+/// memory with [`RegionMap::reserve`].
+pub struct RegionMap<const N: usize> {
+    regions: [MemoryRegion; N],
+    len: usize,
+}
+
+impl<const N: usize> RegionMap<N> {
+    /// Creates a new, empty region map.
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            regions: [MemoryRegion::EMPTY; N],
+            len: 0,
+        }
+    }
+
+    /// Gets all regions in the map, sorted by base address.
+    #[inline(always)]
+    pub fn regions(&self) -> &[MemoryRegion] {
+        &self.regions[..self.len]
+    }
+
+    /// Adds a new region to the map.
+    ///
+    /// The region must not overlap with any regions which are
+    /// already tracked.
+    pub fn insert(&mut self, region: MemoryRegion) -> Result<(), RegionError> {
+        if region.size == 0 || region.base.addr().checked_add(region.size).is_none() {
+            return Err(RegionError::InvalidRegion);
+        }
+
+        // Find the insertion point which keeps the map sorted and
+        // make sure the region fits in between its neighbours.
+        let index = self.regions().partition_point(|r| r.base < region.base);
+        if (index > 0 && self.regions[index - 1].end() > region.base.addr())
+            || (index < self.len && region.end() > self.regions[index].base.addr())
+        {
+            return Err(RegionError::Overlap);
+        }
+
+        self.insert_at(index, region)?;
+        self.coalesce();
+
+        Ok(())
+    }
+
+    /// Carves the range of `size` bytes at `base` out of free
+    /// memory and marks it with the given `kind`.
+    ///
+    /// The range must be fully contained in a single free region,
+    /// which is split up as needed.
+    pub fn reserve(
+        &mut self,
+        base: PhysAddr,
+        size: usize,
+        kind: RegionKind,
+    ) -> Result<(), RegionError> {
+        let start = base.addr();
+        let end = match start.checked_add(size) {
+            Some(end) if size != 0 => end,
+            _ => return Err(RegionError::InvalidRegion),
+        };
+
+        // Find the free region which contains the whole range.
+        let index = self
+            .regions()
+            .iter()
+            .position(|r| r.kind == RegionKind::Free && r.base.addr() <= start && end <= r.end())
+            .ok_or(RegionError::NotFree)?;
+        let free = self.regions[index];
+
+        // Check capacity upfront so that failure leaves the map
+        // untouched. Each remaining free piece takes one entry.
+        let pieces = (free.base.addr() < start) as usize + (end < free.end()) as usize;
+        if self.len + pieces > N {
+            return Err(RegionError::CapacityExceeded);
+        }
+
+        // Replace the free region with up to three pieces.
+        self.regions[index] = free.with_bounds(start, end, kind);
+        if end < free.end() {
+            self.insert_at(
+                index + 1,
+                free.with_bounds(end, free.end(), RegionKind::Free),
+            )?;
+        }
+        if free.base.addr() < start {
+            self.insert_at(
+                index,
+                free.with_bounds(free.base.addr(), start, RegionKind::Free),
+            )?;
+        }
+
+        self.coalesce();
+        Ok(())
+    }
+
+    /// Finds the largest region of free memory in the map.
+    ///
+    /// When there are multiple candidates, the one with the
+    /// lowest base address is returned.
+    pub fn largest_free_region(&self) -> Option<&MemoryRegion> {
+        self.regions()
+            .iter()
+            .filter(|r| r.kind == RegionKind::Free)
+            .rev()
+            .max_by_key(|r| r.size)
+    }
+
+    fn insert_at(&mut self, index: usize, region: MemoryRegion) -> Result<(), RegionError> {
+        if self.len == N {
+            return Err(RegionError::CapacityExceeded);
+        }
+
+        self.regions.copy_within(index..self.len, index + 1);
+        self.regions[index] = region;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn coalesce(&mut self) {
+        let mut i = 1;
+        while i < self.len {
+            let (prev, cur) = (self.regions[i - 1], self.regions[i]);
+            if prev.kind == cur.kind && prev.end() == cur.base.addr() {
+                self.regions[i - 1].size += cur.size;
+                self.regions.copy_within(i + 1..self.len, i);
+                self.len -= 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for RegionMap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start: usize, end: usize, kind: RegionKind) -> MemoryRegion {
+        MemoryRegion::new(PhysAddr::new(start as *mut ()), end - start, kind)
+    }
+
+    fn reserve(map: &mut RegionMap<4>, start: usize, end: usize) -> Result<(), RegionError> {
+        map.reserve(
+            PhysAddr::new(start as *mut ()),
+            end - start,
+            RegionKind::Reserved,
+        )
+    }
+
+    fn free_map(start: usize, end: usize) -> RegionMap<4> {
+        let mut map = RegionMap::new();
+        map.insert(region(start, end, RegionKind::Free)).unwrap();
+        map
+    }
+
+    #[test]
+    fn reserve_middle() {
+        let mut map = free_map(0x1000, 0x5000);
+        reserve(&mut map, 0x2000, 0x3000).unwrap();
+
+        assert_eq!(
+            map.regions(),
+            [
+                region(0x1000, 0x2000, RegionKind::Free),
+                region(0x2000, 0x3000, RegionKind::Reserved),
+                region(0x3000, 0x5000, RegionKind::Free),
+            ]
+        );
+    }
+
+    #[test]
+    fn reserve_edges() {
+        let mut map = free_map(0x1000, 0x5000);
+        reserve(&mut map, 0x1000, 0x2000).unwrap();
+        reserve(&mut map, 0x4000, 0x5000).unwrap();
+
+        assert_eq!(
+            map.regions(),
+            [
+                region(0x1000, 0x2000, RegionKind::Reserved),
+                region(0x2000, 0x4000, RegionKind::Free),
+                region(0x4000, 0x5000, RegionKind::Reserved),
+            ]
+        );
+    }
+
+    #[test]
+    fn reserve_whole_region() {
+        let mut map = free_map(0x1000, 0x5000);
+        reserve(&mut map, 0x1000, 0x5000).unwrap();
+        assert_eq!(
+            map.regions(),
+            [region(0x1000, 0x5000, RegionKind::Reserved)]
+        );
+
+        // Nothing is left to reserve from.
+        assert_eq!(reserve(&mut map, 0x1000, 0x2000), Err(RegionError::NotFree));
+    }
+
+    #[test]
+    fn merge_adjacent() {
+        let mut map = RegionMap::<4>::new();
+        map.insert(region(0x3000, 0x4000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x1000, 0x2000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x4000, 0x5000, RegionKind::Carveout))
+            .unwrap();
+
+        // Filling the gap merges all free memory into one region.
+        map.insert(region(0x2000, 0x3000, RegionKind::Free))
+            .unwrap();
+        assert_eq!(
+            map.regions(),
+            [
+                region(0x1000, 0x4000, RegionKind::Free),
+                region(0x4000, 0x5000, RegionKind::Carveout),
+            ]
+        );
+
+        // Adjacent reservations of the same kind are merged, too.
+        reserve(&mut map, 0x1000, 0x2000).unwrap();
+        reserve(&mut map, 0x2000, 0x3000).unwrap();
+        assert_eq!(
+            map.regions()[0],
+            region(0x1000, 0x3000, RegionKind::Reserved)
+        );
+    }
+
+    #[test]
+    fn reject_overlap() {
+        let mut map = free_map(0x1000, 0x3000);
+        assert_eq!(
+            map.insert(region(0x2000, 0x4000, RegionKind::Carveout)),
+            Err(RegionError::Overlap)
+        );
+        assert_eq!(
+            map.insert(region(0x0, 0x2000, RegionKind::Carveout)),
+            Err(RegionError::Overlap)
+        );
+    }
+
+    #[test]
+    fn capacity_exceeded() {
+        let mut map = RegionMap::<4>::new();
+        map.insert(region(0x1000, 0x5000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x6000, 0x7000, RegionKind::Carveout))
+            .unwrap();
+        map.insert(region(0x8000, 0x9000, RegionKind::Carveout))
+            .unwrap();
+
+        // A 3-way split needs two more entries, but only one is left.
+        assert_eq!(
+            reserve(&mut map, 0x2000, 0x3000),
+            Err(RegionError::CapacityExceeded)
+        );
+        assert_eq!(map.regions().len(), 3);
+        assert_eq!(map.regions()[0], region(0x1000, 0x5000, RegionKind::Free));
+
+        // Reserving at an edge only needs one.
+        reserve(&mut map, 0x1000, 0x2000).unwrap();
+        assert_eq!(
+            map.insert(region(0xA000, 0xB000, RegionKind::Carveout)),
+            Err(RegionError::CapacityExceeded)
+        );
+    }
+
+    #[test]
+    fn largest_free_region() {
+        let mut map = RegionMap::<4>::new();
+        assert_eq!(map.largest_free_region(), None);
+
+        map.insert(region(0x1000, 0x2000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x3000, 0x5000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x6000, 0x8000, RegionKind::Free))
+            .unwrap();
+        map.insert(region(0x9000, 0xC000, RegionKind::Carveout))
+            .unwrap();
+
+        // Of the two largest free regions, the lower one wins.
+        assert_eq!(
+            map.largest_free_region(),
+            Some(&region(0x3000, 0x5000, RegionKind::Free))
+        );
+    }
+}
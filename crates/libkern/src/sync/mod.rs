@@ -0,0 +1,11 @@
+//! Synchronization primitives for the Kernel and its loader.
+//!
+//! These are designed to work without any operating system
+//! support and can thus be used from the earliest stages of
+//! bootstrap on.
+
+mod mutex;
+pub use self::mutex::*;
+
+mod once;
+pub use self::once::*;
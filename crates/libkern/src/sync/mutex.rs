@@ -0,0 +1,203 @@
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::arch::sync::{send_event, wait_for_event};
+
+/// A mutual exclusion primitive based on spinning.
+///
+/// Cores waiting for the lock to be released are put into a
+/// low-power state until the holder signals the release, rather
+/// than hammering the memory bus with atomic operations.
+///
+/// The lock is not fair and does not detect re-entrancy; locking
+/// a mutex again on the same core while a guard is alive will
+/// deadlock.
+pub struct SpinMutex<T: ?Sized> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: The mutex provides exclusive access to the inner value,
+// so it may be shared between cores as long as `T` can be sent.
+unsafe impl<T: ?Sized + Send> Send for SpinMutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for SpinMutex<T> {}
+
+impl<T> SpinMutex<T> {
+    /// Creates a new mutex in unlocked state.
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the mutex and returns the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> SpinMutex<T> {
+    /// Acquires the mutex, waiting until it becomes available.
+    ///
+    /// The lock will be held until the returned guard is dropped.
+    pub fn lock(&self) -> SpinMutexGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+
+            // Wait for the current holder to release the lock. Only
+            // reading the state keeps the cache line shared meanwhile.
+            while self.locked.load(Ordering::Relaxed) {
+                wait_for_event();
+            }
+        }
+    }
+
+    /// Attempts to acquire the mutex without waiting.
+    ///
+    /// Returns [`None`] when the mutex is already locked.
+    #[inline]
+    pub fn try_lock(&self) -> Option<SpinMutexGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| SpinMutexGuard { mutex: self })
+    }
+
+    /// Checks whether the mutex is currently locked.
+    ///
+    /// The result may be outdated by the time it is inspected
+    /// and should only be used for diagnostic purposes.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Gets a mutable reference to the inner value.
+    ///
+    /// No locking is needed since the mutable borrow statically
+    /// guarantees exclusive access.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("SpinMutex");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// An RAII guard for the lock of a [`SpinMutex`].
+///
+/// The lock is released when the guard is dropped. The protected
+/// data is accessible through its [`Deref`] and [`DerefMut`] impls.
+#[must_use = "if unused the SpinMutex will immediately unlock"]
+pub struct SpinMutexGuard<'a, T: ?Sized> {
+    mutex: &'a SpinMutex<T>,
+}
+
+// SAFETY: Sharing the guard only hands out shared references to `T`.
+unsafe impl<T: ?Sized + Sync> Sync for SpinMutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for SpinMutexGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: Holding the guard grants exclusive access.
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for SpinMutexGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: Holding the guard grants exclusive access.
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for SpinMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Drop for SpinMutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+
+        // Wake up all the cores waiting for the lock to be released.
+        send_event();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn mutual_exclusion() {
+        const THREADS: usize = 8;
+        const ITERATIONS: usize = 1000;
+
+        // Each thread performs a non-atomic read-modify-write, so
+        // any overlapping critical sections would lose increments.
+        let mutex = Arc::new(SpinMutex::new((0, false)));
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let mutex = mutex.clone();
+                thread::spawn(move || {
+                    for _ in 0..ITERATIONS {
+                        let mut guard = mutex.lock();
+                        assert!(!guard.1, "two threads hold the lock");
+                        guard.1 = true;
+
+                        let value = guard.0;
+                        thread::yield_now();
+                        guard.0 = value + 1;
+
+                        guard.1 = false;
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mutex = Arc::try_unwrap(mutex).unwrap();
+        assert_eq!(mutex.into_inner(), (THREADS * ITERATIONS, false));
+    }
+
+    #[test]
+    fn try_lock_while_locked() {
+        let mutex = SpinMutex::new(0);
+
+        let guard = mutex.lock();
+        assert!(mutex.is_locked());
+        assert!(mutex.try_lock().is_none());
+
+        drop(guard);
+        assert!(!mutex.is_locked());
+        assert!(mutex.try_lock().is_some());
+    }
+}
@@ -0,0 +1,174 @@
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::arch::sync::{send_event, wait_for_event};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// A cell which is initialized exactly once and then shared.
+///
+/// This is the primitive of choice for global statics which can
+/// only be constructed at runtime. The first caller of
+/// [`Once::call_once`] runs the initializer while all concurrent
+/// callers wait for it to finish, in a low-power state like
+/// [`SpinMutex`][super::SpinMutex] does.
+///
+/// There is no poisoning; when the initializer panics, all other
+/// callers will wait forever.
+pub struct Once<T> {
+    state: AtomicU8,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+// SAFETY: The value is written exactly once before it is shared,
+// so sharing the cell is safe when `T` can be sent and shared.
+unsafe impl<T: Send> Send for Once<T> {}
+unsafe impl<T: Send + Sync> Sync for Once<T> {}
+
+impl<T> Once<T> {
+    /// Creates a new, uninitialized cell.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(INCOMPLETE),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Gets the value of the cell, initializing it with `f` if it
+    /// was not initialized yet.
+    ///
+    /// `f` is guaranteed to be run at most once, even when several
+    /// cores race on this method.
+    pub fn call_once<F: FnOnce() -> T>(&self, f: F) -> &T {
+        if self.try_claim() {
+            // SAFETY: Claiming the cell grants exclusive access.
+            unsafe { (*self.data.get()).write(f()) };
+            self.state.store(COMPLETE, Ordering::Release);
+
+            // Wake up all the cores waiting for the value.
+            send_event();
+        } else {
+            while self.state.load(Ordering::Acquire) != COMPLETE {
+                wait_for_event();
+            }
+        }
+
+        // SAFETY: The cell is initialized at this point.
+        unsafe { self.get_unchecked() }
+    }
+
+    /// Gets the value of the cell, or [`None`] if it was not
+    /// initialized yet.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        // SAFETY: We check that the cell is initialized first.
+        self.is_completed().then(|| unsafe { self.get_unchecked() })
+    }
+
+    /// Checks whether the cell was initialized.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    // Attempts to claim the right to run the initializer.
+    #[inline]
+    fn try_claim(&self) -> bool {
+        self.state
+            .compare_exchange(INCOMPLETE, RUNNING, Ordering::Acquire, Ordering::Acquire)
+            .is_ok()
+    }
+
+    // SAFETY: The cell must be initialized.
+    #[inline]
+    unsafe fn get_unchecked(&self) -> &T {
+        (*self.data.get()).assume_init_ref()
+    }
+}
+
+impl<T> Default for Once<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Once<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Once");
+        match self.get() {
+            Some(data) => d.field("data", data),
+            None => d.field("data", &format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T> Drop for Once<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == COMPLETE {
+            // SAFETY: The cell is initialized and we have exclusive access.
+            unsafe { self.data.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{atomic::AtomicUsize, Arc, Barrier},
+        thread,
+    };
+
+    use super::*;
+
+    #[test]
+    fn initialize_once() {
+        const THREADS: usize = 8;
+
+        let once = Arc::new(Once::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(THREADS));
+
+        let threads: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let (once, calls, barrier) = (once.clone(), calls.clone(), barrier.clone());
+                thread::spawn(move || {
+                    // Release all threads at once to make them race.
+                    barrier.wait();
+                    *once.call_once(|| {
+                        calls.fetch_add(1, Ordering::Relaxed);
+                        thread::yield_now();
+                        i
+                    })
+                })
+            })
+            .collect();
+        let mut values = Vec::new();
+        for thread in threads {
+            values.push(thread.join().unwrap());
+        }
+
+        // Every thread sees the value of the single initializer run.
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert!(values.iter().all(|&v| v == values[0]));
+        assert_eq!(once.get(), Some(&values[0]));
+    }
+
+    #[test]
+    fn get_uninitialized() {
+        let once = Once::new();
+        assert!(!once.is_completed());
+        assert_eq!(once.get(), None);
+
+        assert_eq!(*once.call_once(|| 1), 1);
+        assert_eq!(*once.call_once(|| 2), 1);
+        assert!(once.is_completed());
+    }
+}
@@ -1,9 +1,8 @@
 //! Implementation details used by macros.
 
-use core::{
-    arch::asm,
-    fmt::{self, Write},
-};
+#[cfg(not(feature = "mock"))]
+use core::arch::asm;
+use core::fmt::{self, Write};
 
 use crate::host::HostStream;
 
@@ -35,11 +34,7 @@ pub fn hstdout_fmt(args: fmt::Arguments) -> Result<(), ()> {
                 HSTDOUT = Some(HostStream::stdout()?);
             }
 
-            HSTDOUT
-                .as_mut()
-                .unwrap_unchecked()
-                .write_fmt(args)
-                .map_err(drop)
+            HSTDOUT.as_mut().unwrap_unchecked().write_fmt_buffered(args)
         })
     }
 }
@@ -69,15 +64,12 @@ pub fn hstderr_fmt(args: fmt::Arguments) -> Result<(), ()> {
                 HSTDERR = Some(HostStream::stderr()?);
             }
 
-            HSTDERR
-                .as_mut()
-                .unwrap_unchecked()
-                .write_fmt(args)
-                .map_err(drop)
+            HSTDERR.as_mut().unwrap_unchecked().write_fmt_buffered(args)
         })
     }
 }
 
+#[cfg(not(feature = "mock"))]
 #[inline(always)]
 unsafe fn interrupt_free<F, R>(f: F) -> R
 where
@@ -110,3 +102,13 @@ where
 
     result
 }
+
+#[cfg(feature = "mock")]
+#[inline(always)]
+unsafe fn interrupt_free<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    // There are no interrupts to mask on the host.
+    f()
+}
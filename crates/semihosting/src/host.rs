@@ -1,9 +1,35 @@
 //! Host I/O operations.
 
-use core::{fmt, slice};
+use core::{
+    fmt::{self, Write},
+    slice,
+};
 
 use crate::ops;
 
+// The number of bytes of formatted output which are collected
+// before they are passed to the host.
+const BUFFER_SIZE: usize = 128;
+
+/// Writes formatted text to the host's standard output.
+///
+/// The text is buffered so that it is passed to the host in as
+/// few operations as possible. This backs the [`hprint!`][crate::hprint]
+/// family of macros, which can be used as `semihosting::hprintln!`
+/// without importing them first.
+#[allow(clippy::result_unit_err)]
+pub fn print_fmt(args: fmt::Arguments) -> Result<(), ()> {
+    crate::export::hstdout_fmt(args)
+}
+
+/// Writes formatted text to the host's standard error.
+///
+/// See [`print_fmt`] for details.
+#[allow(clippy::result_unit_err)]
+pub fn eprint_fmt(args: fmt::Arguments) -> Result<(), ()> {
+    crate::export::hstderr_fmt(args)
+}
+
 /// A byte stream to host, e.g. host's stdout or stderr.
 #[derive(Clone, Copy)]
 pub struct HostStream {
@@ -33,6 +59,43 @@ impl HostStream {
     pub fn write_all(&mut self, buffer: &[u8]) -> Result<(), ()> {
         write_all(self.fd, buffer)
     }
+
+    /// Writes formatted text into this sink.
+    ///
+    /// Unlike going through the [`fmt::Write`] implementation, this
+    /// collects the output in a buffer first rather than issuing one
+    /// operation for every formatted piece.
+    #[allow(clippy::result_unit_err)]
+    pub fn write_fmt_buffered(&mut self, args: fmt::Arguments) -> Result<(), ()> {
+        let mut writer = BufferedWriter {
+            stream: self,
+            buffer: [0; BUFFER_SIZE],
+            len: 0,
+        };
+
+        writer.write_fmt(args).map_err(drop)?;
+        writer.flush()
+    }
+
+    /// Checks whether this stream is connected to an interactive
+    /// device, such as the host's terminal.
+    ///
+    /// The host reports errors with a return value of -1; these
+    /// are mapped to `Err(())`.
+    #[allow(clippy::result_unit_err)]
+    pub fn is_tty(&self) -> Result<bool, ()> {
+        istty(self.fd)
+    }
+
+    /// Gets the length in bytes of the file behind this stream.
+    ///
+    /// The host reports errors with a return value of -1; these
+    /// are mapped to `Err(())`. Notably, this is the case when
+    /// the stream is not connected to a file.
+    #[allow(clippy::result_unit_err)]
+    pub fn file_len(&self) -> Result<usize, ()> {
+        flen(self.fd)
+    }
 }
 
 impl fmt::Write for HostStream {
@@ -41,6 +104,38 @@ impl fmt::Write for HostStream {
     }
 }
 
+struct BufferedWriter<'a> {
+    stream: &'a mut HostStream,
+    buffer: [u8; BUFFER_SIZE],
+    len: usize,
+}
+
+impl BufferedWriter<'_> {
+    fn flush(&mut self) -> Result<(), ()> {
+        self.stream.write_all(&self.buffer[..self.len])?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl fmt::Write for BufferedWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            if self.len == BUFFER_SIZE {
+                self.flush().map_err(|_| fmt::Error)?;
+            }
+
+            let n = bytes.len().min(BUFFER_SIZE - self.len);
+            self.buffer[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+            self.len += n;
+            bytes = &bytes[n..];
+        }
+
+        Ok(())
+    }
+}
+
 fn open(name: &str, mode: usize) -> Result<HostStream, ()> {
     let name = name.as_bytes();
     match unsafe { syscall!(OPEN, name.as_ptr(), mode, name.len() - 1) } as isize {
@@ -49,6 +144,21 @@ fn open(name: &str, mode: usize) -> Result<HostStream, ()> {
     }
 }
 
+fn istty(fd: usize) -> Result<bool, ()> {
+    match unsafe { syscall!(ISTTY, fd) } as isize {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(()),
+    }
+}
+
+fn flen(fd: usize) -> Result<usize, ()> {
+    match unsafe { syscall!(FLEN, fd) } as isize {
+        -1 => Err(()),
+        len => Ok(len as usize),
+    }
+}
+
 fn write_all(fd: usize, mut buffer: &[u8]) -> Result<(), ()> {
     while !buffer.is_empty() {
         match unsafe { syscall!(WRITE, fd, buffer.as_ptr(), buffer.len()) } {
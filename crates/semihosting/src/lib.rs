@@ -20,10 +20,21 @@
 //! macro for user-friendly construction and printing of formatted
 //! strings.
 //!
+//! The [`hprint!`], [`hprintln!`], [`heprint!`] and [`heprintln!`]
+//! macros are exported at the crate root and can be invoked with
+//! their full path, e.g. `semihosting::hprintln!("...")`. They are
+//! backed by [`host::print_fmt`] and [`host::eprint_fmt`].
+//!
 //! Since semihosting operations are modeled as [system calls][sc],
 //! this crate exposes an untyped [`syscall!`] interface just like
 //! the [`sc`] crate does.
 //!
+//! # Testing
+//!
+//! With the `mock` feature enabled, operations are not trapped
+//! into the debugger but recorded by the `mock` backend. This
+//! makes code that uses semihosting testable on the host.
+//!
 //! # Forewarning
 //!
 //! Semihosting operations are *very* slow. Like, each WRITE operation
@@ -41,10 +52,11 @@
 //! [`sc`]: https://crates.io/crates/sc
 //! [pdf]: http://infocenter.arm.com/help/topic/com.arm.doc.dui0471e/DUI0471E_developing_for_arm_processors.pdf
 
-#![no_std]
+#![cfg_attr(not(feature = "mock"), no_std)]
 #![deny(rustdoc::broken_intra_doc_links)]
 #![feature(strict_provenance)]
 
+#[cfg(not(feature = "mock"))]
 use core::arch::asm;
 
 #[macro_use]
@@ -54,6 +66,8 @@ pub mod debug;
 #[doc(hidden)]
 pub mod export;
 pub mod host;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod ops;
 
 /// Performs a semihosting operation, takes a pointer to an
@@ -75,6 +89,7 @@ pub unsafe fn syscall<T>(nr: usize, arg: &T) -> usize {
 ///
 /// - `nr` must be a valid syscall from [`crate::ops`].
 /// - `arg` must point to a valid argument block for the syscall.
+#[cfg(not(feature = "mock"))]
 #[inline(always)]
 pub unsafe fn syscall1(mut nr: usize, arg: usize) -> usize {
     asm!(
@@ -86,3 +101,16 @@ pub unsafe fn syscall1(mut nr: usize, arg: usize) -> usize {
 
     nr
 }
+
+/// Performs a semihosting operation, takes one integer as an
+/// argument.
+///
+/// # Safety
+///
+/// - `nr` must be a valid syscall from [`crate::ops`].
+/// - `arg` must point to a valid argument block for the syscall.
+#[cfg(feature = "mock")]
+#[inline(always)]
+pub unsafe fn syscall1(nr: usize, arg: usize) -> usize {
+    mock::record(nr, arg)
+}
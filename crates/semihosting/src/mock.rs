@@ -0,0 +1,87 @@
+//! A host-side backend which records semihosting operations.
+//!
+//! This replaces the `hlt` trap when the `mock` feature is
+//! enabled. Every operation is logged for the current thread
+//! and can be inspected with [`take_operations`].
+//!
+//! # Example
+//!
+//! ```
+//! # use saturnus_semihosting::{hprint, mock, ops};
+//!
+//! hprint!("Hello");
+//!
+//! let operations = mock::take_operations();
+//! assert_eq!(operations.last().unwrap().nr, ops::WRITE);
+//! assert_eq!(operations.last().unwrap().data, b"Hello");
+//! ```
+
+use std::{cell::RefCell, ffi::CStr, os::raw::c_char, slice};
+
+use crate::ops;
+
+thread_local! {
+    static OPERATIONS: RefCell<Vec<Operation>> = RefCell::new(Vec::new());
+}
+
+/// A semihosting operation recorded by the mock backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operation {
+    /// The operation number, see [`crate::ops`].
+    pub nr: usize,
+    /// The raw argument to the operation.
+    pub arg: usize,
+    /// The bytes which were output by `WRITE`, `WRITE0` or
+    /// `WRITEC` operations; empty for everything else.
+    pub data: Vec<u8>,
+}
+
+/// Takes all operations recorded on the current thread so far.
+pub fn take_operations() -> Vec<Operation> {
+    OPERATIONS.with(|ops| ops.take())
+}
+
+/// Records an operation and pretends that it succeeded.
+///
+/// # Safety
+///
+/// `arg` must be valid for the operation `nr`, as if it were
+/// passed to the real trap.
+pub(crate) unsafe fn record(nr: usize, arg: usize) -> usize {
+    let data = match nr {
+        ops::WRITE => {
+            let block = arg as *const usize;
+            slice::from_raw_parts(*block.add(1) as *const u8, *block.add(2)).to_vec()
+        }
+        ops::WRITE0 => CStr::from_ptr(arg as *const c_char).to_bytes().to_vec(),
+        ops::WRITEC => Vec::from([*(arg as *const u8)]),
+        _ => Vec::new(),
+    };
+
+    OPERATIONS.with(|ops| ops.borrow_mut().push(Operation { nr, arg, data }));
+
+    // All operations report success with a zero value. For OPEN,
+    // this is a valid handle and for WRITE, no bytes are left.
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::HostStream;
+
+    #[test]
+    fn record_write() {
+        let mut stdout = HostStream::stdout().unwrap();
+        stdout.write_all(b"Saturnus").unwrap();
+
+        let operations = take_operations();
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].nr, ops::OPEN);
+        assert_eq!(operations[1].nr, ops::WRITE);
+        assert_eq!(operations[1].data, b"Saturnus");
+
+        // The log is drained by taking the operations.
+        assert!(take_operations().is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! Runtime assertions for debug builds.
+
+#[cfg(debug_assertions)]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Tracks the completion of up to 32 sequential setup steps.
+///
+/// Every step is identified by its index and marked complete
+/// through [`StepGuard::assert_step`], which panics in debug
+/// builds when a step runs more than once or out of order.
+/// In release builds, the guard compiles to nothing.
+///
+/// The steps are expected to run on a single core. Only plain
+/// loads and stores are used, so the guard also works before
+/// the MMU is enabled, where exclusive accesses may not be
+/// supported.
+pub struct StepGuard {
+    #[cfg(debug_assertions)]
+    done: AtomicU32,
+}
+
+impl StepGuard {
+    /// Creates a new guard with no completed steps.
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(debug_assertions)]
+            done: AtomicU32::new(0),
+        }
+    }
+
+    /// Marks `step` as complete, after checking that exactly the
+    /// steps in `expected_prior_mask` were completed before.
+    ///
+    /// Bit `n` of the mask corresponds to the step with index `n`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics when `step` was already completed
+    /// or when the completed steps do not match the expectation.
+    #[inline(always)]
+    pub fn assert_step(&self, expected_prior_mask: u32, step: u32) {
+        #[cfg(debug_assertions)]
+        {
+            let bit = 1 << step;
+            let done = self.done.load(Ordering::Relaxed);
+
+            assert!(done & bit == 0, "step {step} ran more than once");
+            assert!(
+                done == expected_prior_mask,
+                "step {step} ran out of order: expected steps {expected_prior_mask:#b}, got {done:#b}"
+            );
+
+            self.done.store(done | bit, Ordering::Relaxed);
+        }
+
+        #[cfg(not(debug_assertions))]
+        let _ = (expected_prior_mask, step);
+    }
+}
+
+impl Default for StepGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
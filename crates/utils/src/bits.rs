@@ -0,0 +1,47 @@
+//! Helpers for bit manipulation.
+
+/// Builds a mask of `len` consecutive set bits, starting at bit
+/// `start`.
+///
+/// A `len` of zero produces an empty mask, and masks may extend
+/// all the way up to the most significant bit.
+///
+/// # Panics
+///
+/// Panics when `start + len` exceeds the bit width of [`usize`].
+#[inline(always)]
+pub const fn bitmask(start: u32, len: u32) -> usize {
+    assert!(start + len <= usize::BITS);
+
+    // Shift the all-ones value right instead of shifting a single
+    // bit left so that full-width masks don't overflow.
+    match len {
+        0 => 0,
+        len => (usize::MAX >> (usize::BITS - len)) << start,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_and_full_masks() {
+        assert_eq!(bitmask(5, 0), 0);
+        assert_eq!(bitmask(usize::BITS, 0), 0);
+        assert_eq!(bitmask(0, usize::BITS), usize::MAX);
+    }
+
+    #[test]
+    fn partial_masks() {
+        assert_eq!(bitmask(0, 48), 0x0000_FFFF_FFFF_FFFF);
+        assert_eq!(bitmask(60, 4), 0xF000_0000_0000_0000);
+        assert_eq!(bitmask(4, 12), 0xFFF0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_out_of_range() {
+        bitmask(60, 5);
+    }
+}
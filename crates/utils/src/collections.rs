@@ -0,0 +1,230 @@
+//! Heap-free collection types.
+
+use core::{
+    fmt,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr, slice,
+};
+
+/// A vector with a fixed capacity of `N` elements.
+///
+/// The elements are stored inline, so this is usable where no
+/// allocator is available. Operations which would exceed the
+/// capacity fail instead of reallocating.
+///
+/// Through [`Deref`], all the methods of `[T]` are available,
+/// including indexing and iteration.
+pub struct ArrayVec<T, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    /// Creates a new, empty vector.
+    pub const fn new() -> Self {
+        Self {
+            // SAFETY: An array of `MaybeUninit` needs no initialization.
+            data: unsafe { MaybeUninit::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    /// Gets the number of elements in the vector.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the vector holds no elements.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the total number of elements the vector can hold.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Checks if the vector is filled up to its capacity.
+    #[inline]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Appends `value` to the back of the vector.
+    ///
+    /// When the vector is full, `value` is handed back as the
+    /// error.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+
+        self.data[self.len].write(value);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Removes the last element from the vector and returns it,
+    /// or [`None`] if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.len -= 1;
+        // SAFETY: The element was initialized and is no longer
+        // tracked by `len`, so it won't be read again.
+        Some(unsafe { self.data[self.len].assume_init_read() })
+    }
+
+    /// Removes all elements from the vector.
+    pub fn clear(&mut self) {
+        let elems: *mut [T] = self.as_mut_slice();
+
+        // Reset the length first so that a panicking destructor
+        // cannot cause a double drop.
+        self.len = 0;
+        // SAFETY: The elements were initialized and are no longer
+        // tracked by `len`.
+        unsafe { ptr::drop_in_place(elems) }
+    }
+
+    /// Gets a slice of all the elements in the vector.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.data.as_ptr().cast::<T>(), self.len) }
+    }
+
+    /// Gets a mutable slice of all the elements in the vector.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: The first `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    // Counts how often values sharing the same counter are dropped.
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn push_full() {
+        let mut vec = ArrayVec::<u32, 2>::new();
+        assert_eq!(vec.push(1), Ok(()));
+        assert_eq!(vec.push(2), Ok(()));
+        assert!(vec.is_full());
+
+        assert_eq!(vec.push(3), Err(3));
+        assert_eq!(vec.as_slice(), [1, 2]);
+    }
+
+    #[test]
+    fn pop_empty() {
+        let mut vec = ArrayVec::<u32, 2>::new();
+        assert_eq!(vec.pop(), None);
+
+        vec.push(1).unwrap();
+        assert_eq!(vec.pop(), Some(1));
+        assert_eq!(vec.pop(), None);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn drop_initialized_once() {
+        let drops = Rc::new(Cell::new(0));
+
+        // Only the initialized elements of a partially filled
+        // vector are dropped.
+        let mut vec = ArrayVec::<_, 4>::new();
+        for _ in 0..3 {
+            vec.push(DropCounter(drops.clone())).ok().unwrap();
+        }
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+
+        // Elements removed by `clear` are not dropped again along
+        // with the vector, and neither are popped ones.
+        drops.set(0);
+        let mut vec = ArrayVec::<_, 4>::new();
+        for _ in 0..4 {
+            vec.push(DropCounter(drops.clone())).ok().unwrap();
+        }
+        vec.clear();
+        assert_eq!(drops.get(), 4);
+        vec.push(DropCounter(drops.clone())).ok().unwrap();
+        vec.push(DropCounter(drops.clone())).ok().unwrap();
+        drop(vec.pop());
+        assert_eq!(drops.get(), 5);
+        drop(vec);
+        assert_eq!(drops.get(), 6);
+    }
+}
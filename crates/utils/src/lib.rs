@@ -1,5 +1,10 @@
 //! Common utilities for the Saturnus project.
 
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 pub mod align;
+pub mod assert;
+pub mod bits;
+pub mod collections;
+pub mod mem;
+pub mod units;
@@ -0,0 +1,200 @@
+//! Helpers for raw memory access.
+
+use core::{
+    mem::{align_of, size_of},
+    ptr, slice,
+};
+
+use crate::align::is_aligned;
+
+/// Reads a value of type `T` from `ptr` without requiring it to
+/// be aligned.
+///
+/// This never forms a reference to the pointed-to data, so it is
+/// the right tool for parsing values at arbitrary byte offsets.
+///
+/// # Safety
+///
+/// - `ptr` must be valid for reads of `size_of::<T>()` bytes.
+/// - The bytes must form a valid value of type `T`.
+///
+/// Ownership semantics are the same as for [`ptr::read`].
+#[inline(always)]
+pub unsafe fn read_unaligned<T>(ptr: *const u8) -> T {
+    ptr::read_unaligned(ptr.cast::<T>())
+}
+
+/// Writes `val` to `ptr` without requiring it to be aligned.
+///
+/// The previous value at `ptr` is overwritten without being
+/// dropped.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `size_of::<T>()` bytes.
+#[inline(always)]
+pub unsafe fn write_unaligned<T>(ptr: *mut u8, val: T) {
+    ptr::write_unaligned(ptr.cast::<T>(), val)
+}
+
+/// Forms a slice of `len` values of type `T` at `ptr`, after
+/// checking that it is well-formed and ends no later than the
+/// address `region_end`.
+///
+/// Returns [`None`] when `ptr` is null or not aligned for `T`,
+/// when the size of the slice overflows, or when the slice would
+/// extend past `region_end`.
+///
+/// # Safety
+///
+/// The memory from `ptr` up to `region_end` must be valid for
+/// reads and hold initialized values of type `T`. It must not be
+/// mutated for the lifetime `'a`, except through [`UnsafeCell`].
+///
+/// [`UnsafeCell`]: core::cell::UnsafeCell
+#[inline]
+pub unsafe fn slice_from_checked<'a, T>(
+    ptr: *const T,
+    len: usize,
+    region_end: usize,
+) -> Option<&'a [T]> {
+    let start = ptr as usize;
+    if ptr.is_null() || !is_aligned(start, align_of::<T>()) {
+        return None;
+    }
+
+    let end = len
+        .checked_mul(size_of::<T>())
+        .filter(|&size| size <= isize::MAX as usize)
+        .and_then(|size| start.checked_add(size))?;
+    (end <= region_end).then(|| slice::from_raw_parts(ptr, len))
+}
+
+/// Gets the size of a type `T` in bytes.
+///
+/// This is [`size_of`] under a name that pairs with
+/// [`bit_size_of`]. Zero-sized types have a size of 0.
+#[inline(always)]
+pub const fn byte_size_of<T>() -> usize {
+    size_of::<T>()
+}
+
+/// Gets the size of a type `T` in bits.
+///
+/// Like [`byte_size_of`], this can be used in `const` contexts and
+/// accounts for the padding of composite types.
+#[inline(always)]
+pub const fn bit_size_of<T>() -> usize {
+    byte_size_of::<T>() * u8::BITS as usize
+}
+
+const _: () = assert!(byte_size_of::<()>() == 0 && bit_size_of::<()>() == 0);
+const _: () = assert!(bit_size_of::<u64>() == 64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The `u8` is padded to the alignment of the `u32`.
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Padded {
+        a: u8,
+        b: u32,
+    }
+
+    #[test]
+    fn slice_in_region() {
+        let data = [1u32, 2, 3, 4];
+        let end = data.as_ptr_range().end as usize;
+
+        let slice = unsafe { slice_from_checked(data.as_ptr(), 4, end) };
+        assert_eq!(slice, Some(&data[..]));
+        let slice = unsafe { slice_from_checked(data.as_ptr(), 0, end) };
+        assert_eq!(slice, Some(&[][..]));
+    }
+
+    #[test]
+    fn reject_past_region_end() {
+        let data = [1u32, 2, 3, 4];
+        let end = data.as_ptr_range().end as usize;
+
+        assert_eq!(unsafe { slice_from_checked(data.as_ptr(), 5, end) }, None);
+        assert_eq!(
+            unsafe { slice_from_checked(data.as_ptr(), 4, end - 1) },
+            None
+        );
+    }
+
+    #[test]
+    fn reject_overflow() {
+        let data = [1u32, 2, 3, 4];
+        let ptr = data.as_ptr();
+
+        // The size of the slice itself overflows.
+        let len = usize::MAX / size_of::<u32>() + 1;
+        assert_eq!(unsafe { slice_from_checked(ptr, len, usize::MAX) }, None);
+        // The size fits, but exceeds `isize::MAX`.
+        let len = isize::MAX as usize / size_of::<u32>() + 1;
+        assert_eq!(unsafe { slice_from_checked(ptr, len, usize::MAX) }, None);
+        // The end address wraps around.
+        let len = isize::MAX as usize / size_of::<u32>();
+        let ptr = (usize::MAX - 0xF) as *const u32;
+        assert_eq!(unsafe { slice_from_checked(ptr, len, usize::MAX) }, None);
+    }
+
+    #[test]
+    fn reject_misaligned() {
+        let data = [1u32, 2, 3, 4];
+        let end = data.as_ptr_range().end as usize;
+        let ptr = data.as_ptr().cast::<u8>().wrapping_add(1).cast::<u32>();
+
+        assert_eq!(unsafe { slice_from_checked(ptr, 1, end) }, None);
+        assert_eq!(
+            unsafe { slice_from_checked(ptr::null::<u32>(), 0, end) },
+            None
+        );
+    }
+
+    #[test]
+    fn unaligned_round_trip() {
+        for offset in [1, 3] {
+            let mut data = [0xEEu8; 16];
+            unsafe {
+                write_unaligned(data.as_mut_ptr().add(offset), 0x1122_3344u32);
+                assert_eq!(
+                    read_unaligned::<u32>(data.as_ptr().add(offset)),
+                    0x1122_3344
+                );
+            }
+
+            // The surrounding bytes are left untouched.
+            assert_eq!(data[offset..offset + 4], 0x1122_3344u32.to_ne_bytes());
+            assert!(data[..offset].iter().all(|&b| b == 0xEE));
+            assert!(data[offset + 4..].iter().all(|&b| b == 0xEE));
+
+            let mut data = [0xEEu8; 16];
+            let value = 0x0102_0304_0506_0708u64;
+            unsafe {
+                write_unaligned(data.as_mut_ptr().add(offset), value);
+                assert_eq!(read_unaligned::<u64>(data.as_ptr().add(offset)), value);
+            }
+
+            assert_eq!(data[offset..offset + 8], value.to_ne_bytes());
+            assert!(data[..offset].iter().all(|&b| b == 0xEE));
+            assert!(data[offset + 8..].iter().all(|&b| b == 0xEE));
+        }
+    }
+
+    #[test]
+    fn type_sizes() {
+        assert_eq!(byte_size_of::<()>(), 0);
+        assert_eq!(bit_size_of::<()>(), 0);
+
+        assert_eq!(byte_size_of::<u64>(), 8);
+        assert_eq!(bit_size_of::<u64>(), 64);
+
+        assert_eq!(byte_size_of::<Padded>(), 8);
+        assert_eq!(bit_size_of::<Padded>(), 64);
+    }
+}
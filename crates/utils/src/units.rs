@@ -0,0 +1,78 @@
+//! Helpers for working with binary size units.
+
+use core::fmt;
+
+const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Converts a number of KiB to bytes.
+#[inline(always)]
+pub const fn kib(value: usize) -> usize {
+    value << 10
+}
+
+/// Converts a number of MiB to bytes.
+#[inline(always)]
+pub const fn mib(value: usize) -> usize {
+    value << 20
+}
+
+/// Converts a number of GiB to bytes.
+#[inline(always)]
+pub const fn gib(value: usize) -> usize {
+    value << 30
+}
+
+/// Formats a byte count in the largest binary unit it fills.
+///
+/// Counts of at least 1 KiB are printed with one decimal place,
+/// rounded down, e.g. `1.5 MiB`. Smaller counts are printed as
+/// plain bytes, e.g. `512 B`.
+///
+/// This does not allocate and is thus usable in any context.
+#[inline]
+pub fn fmt_bytes(value: usize) -> impl fmt::Display {
+    ByteCount(value)
+}
+
+struct ByteCount(usize);
+
+impl fmt::Display for ByteCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Find the largest unit which is not larger than the value.
+        let bits = usize::BITS - self.0.leading_zeros();
+        let unit = (bits.saturating_sub(1) / 10) as usize;
+
+        if unit == 0 {
+            return write!(f, "{} {}", self.0, UNITS[0]);
+        }
+
+        // Split into the whole part and the first decimal digit.
+        // The remainder is less than `1 << shift`, so multiplying
+        // it by 10 cannot overflow for any of our units.
+        let shift = unit * 10;
+        let whole = self.0 >> shift;
+        let fraction = ((self.0 & ((1 << shift) - 1)) * 10) >> shift;
+
+        write!(f, "{whole}.{fraction} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_bytes() {
+        assert_eq!(fmt_bytes(0).to_string(), "0 B");
+        assert_eq!(fmt_bytes(1023).to_string(), "1023 B");
+        assert_eq!(fmt_bytes(1024).to_string(), "1.0 KiB");
+    }
+
+    #[test]
+    fn format_larger_units() {
+        assert_eq!(fmt_bytes(kib(1) + 512).to_string(), "1.5 KiB");
+        assert_eq!(fmt_bytes(mib(2) - 1).to_string(), "1.9 MiB");
+        assert_eq!(fmt_bytes(gib(3)).to_string(), "3.0 GiB");
+        assert_eq!(fmt_bytes(usize::MAX).to_string(), "15.9 EiB");
+    }
+}
@@ -0,0 +1,12 @@
+//! Introspection of the current Exception Level.
+
+use cortex_a::registers::CurrentEL;
+use tock_registers::interfaces::Readable;
+
+/// Gets the Exception Level the loader is executing under.
+///
+/// The result is always in the range of `0..=3`.
+#[inline(always)]
+pub fn current_el() -> u8 {
+    CurrentEL.read(CurrentEL::EL) as u8
+}
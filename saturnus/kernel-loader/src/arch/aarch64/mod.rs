@@ -3,4 +3,13 @@
 //! Everything is re-exported by the unifying
 //! [`crate::arch`] module.
 
+pub mod el;
+pub use self::el::current_el;
+
 pub mod r0;
+
+// Only needed to report the stack pointer on panic in debug builds.
+#[cfg(all(debug_assertions, feature = "qemu"))]
+pub mod sp;
+#[cfg(all(debug_assertions, feature = "qemu"))]
+pub use self::sp::stack_pointer;
@@ -8,6 +8,8 @@ global_asm!(include_str!("exception.s"));
 
 struct EsrEl1;
 
+struct SErrorSyndrome;
+
 #[repr(transparent)]
 struct SpsrEl1(InMemoryRegister<u64, SPSR_EL1::Register>);
 
@@ -45,6 +47,18 @@ unsafe extern "C" fn undefined_exception_handler(ctx: &ExceptionContext) -> ! {
     )
 }
 
+#[no_mangle]
+unsafe extern "C" fn serror_exception_handler(ctx: &ExceptionContext) -> ! {
+    // SErrors are asynchronous and do not update FAR_EL1, so
+    // there is no meaningful fault address to report.
+    unimplemented!(
+        "An SError occurred!\n{}\n{}\n{}",
+        EsrEl1,
+        SErrorSyndrome,
+        ctx
+    )
+}
+
 impl fmt::Display for EsrEl1 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use ESR_EL1::EC::Value::*;
@@ -108,6 +122,47 @@ impl fmt::Display for EsrEl1 {
     }
 }
 
+impl fmt::Display for SErrorSyndrome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let iss = ESR_EL1.read(ESR_EL1::ISS);
+
+        // With IDS set, the remaining bits hold an implementation
+        // defined syndrome which we cannot decode any further.
+        if iss & (1 << 24) != 0 {
+            return write!(f, "SError syndrome: implementation defined ({:#x})", iss);
+        }
+
+        writeln!(f, "SError syndrome: architectural")?;
+        writeln!(
+            f,
+            "    External Abort Type           (EA):  {}",
+            if iss & (1 << 9) != 0 {
+                "External abort"
+            } else {
+                "Other"
+            }
+        )?;
+        writeln!(
+            f,
+            "    Asynchronous Error Type       (AET): {:#x} ({})",
+            (iss >> 10) & 0b111,
+            match (iss >> 10) & 0b111 {
+                0b000 => "Uncontainable",
+                0b001 => "Unrecoverable",
+                0b010 => "Restartable",
+                0b011 => "Recoverable",
+                0b110 => "Corrected",
+                _ => "Reserved",
+            }
+        )?;
+        write!(
+            f,
+            "    Data Fault Status Code        (DFSC): {:#x}",
+            iss & 0b11_1111
+        )
+    }
+}
+
 #[inline]
 fn stringify_flag(set: bool) -> &'static str {
     if set {
@@ -87,7 +87,7 @@ VECTOR_ENTRY current_el0_fiq
 END_VECTOR_ENTRY current_el0_fiq
 
 VECTOR_ENTRY current_el0_serror
-    DISPATCH_WITH_CONTEXT undefined_exception_handler
+    DISPATCH_WITH_CONTEXT serror_exception_handler
 END_VECTOR_ENTRY current_el0_serror
 
 // Current exception level with SP_ELx where x > 0.
@@ -106,7 +106,7 @@ VECTOR_ENTRY current_elx_fiq
 END_VECTOR_ENTRY current_elx_fiq
 
 VECTOR_ENTRY current_elx_serror
-    DISPATCH_WITH_CONTEXT undefined_exception_handler
+    DISPATCH_WITH_CONTEXT serror_exception_handler
 END_VECTOR_ENTRY current_elx_serror
 
 // Lower exception level, AArch64.
@@ -124,7 +124,7 @@ VECTOR_ENTRY lower_aarch64_fiq
 END_VECTOR_ENTRY lower_aarch64_fiq
 
 VECTOR_ENTRY lower_aarch64_serror
-    DISPATCH_WITH_CONTEXT undefined_exception_handler
+    DISPATCH_WITH_CONTEXT serror_exception_handler
 END_VECTOR_ENTRY lower_aarch64_serror
 
 // Lower exception level, AArch32.
@@ -142,5 +142,5 @@ VECTOR_ENTRY lower_aarch32_fiq
 END_VECTOR_ENTRY lower_aarch32_fiq
 
 VECTOR_ENTRY lower_aarch32_serror
-    DISPATCH_WITH_CONTEXT undefined_exception_handler
+    DISPATCH_WITH_CONTEXT serror_exception_handler
 END_VECTOR_ENTRY lower_aarch32_serror
@@ -20,6 +20,8 @@ __saturnus_loader_version:
     .word 0xFFFFFFFF
 __saturnus_loader_marker:
     .word 0xCCCCCCCC
+__saturnus_loader_size:
+    .word 0x00000000  // Filled in by kernel-image.
 
 // fn __saturnus_loader_main(
 //     kernel_base: *mut u8,
@@ -41,14 +43,25 @@ __saturnus_loader_main:
     LOAD_LABEL_ADDR x16, x18, __saturnus_loader_bss_start
     LOAD_LABEL_ADDR x17, x18, __saturnus_loader_bss_end
 
-    // Clear every byte pair in .bss section.
+    // Clear the .bss section in chunks of 16 bytes for as long
+    // as a whole chunk fits before the end of the section.
 0:
-    cmp x16, x17
-    b.cs 1f
+    add x15, x16, #16
+    cmp x15, x17
+    b.hi 1f
     stp xzr, xzr, [x16], #16
     b 0b
 
+    // Clear the remaining tail of less than 16 bytes bytewise.
+    // The linker script keeps the section size 16-byte aligned,
+    // so this is only a safety net.
 1:
+    cmp x16, x17
+    b.cs 2f
+    strb wzr, [x16], #1
+    b 1b
+
+2:
     // Set the stack to the end of the initialized .bss section.
     LOAD_LABEL_ADDR x17, x18, __saturnus_loader_stack_top
     mov sp, x17
@@ -63,6 +76,9 @@ __saturnus_loader_main:
     LOAD_LABEL_ADDR x1, x0, __saturnus_loader_dynamic_start
     bl apply_relocations
 
+    // Report a diagnostic if relocation did not succeed.
+    cbnz x0, report_relocation_failure
+
     // Setup exception handling for catching runtime errors.
     msr tpidr_el1, xzr
     msr cntv_cval_el0, xzr
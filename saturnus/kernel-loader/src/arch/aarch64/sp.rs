@@ -0,0 +1,10 @@
+//! Introspection of the current stack pointer.
+
+use cortex_a::registers::SP;
+use tock_registers::interfaces::Readable;
+
+/// Gets the current value of the stack pointer.
+#[inline(always)]
+pub fn stack_pointer() -> usize {
+    SP.get() as usize
+}
@@ -5,8 +5,14 @@
 #[path = "qemu/mod.rs"]
 mod bsp_impl;
 
+/// The name of the board the Kernel Loader is built for.
+pub const NAME: &str = bsp_impl::NAME;
+
 /// Target system control and configuration.
 pub mod system_control {
+    use libkern::BUILD_CONFIG;
+    use utils::align::is_aligned;
+
     use super::bsp_impl;
 
     /// Takes the physical Kernel base and determines from target
@@ -14,7 +20,26 @@ pub mod system_control {
     ///
     /// Based on the decision, this function either returns [`Some`]
     /// with the newly determined base pointer, or [`None`].
+    ///
+    /// Implementations must only return bases which are aligned to
+    /// the page size, since the Kernel's segments are mapped from
+    /// there.
     pub fn adjust_kernel_base(base: *mut u8) -> Option<*mut u8> {
-        bsp_impl::system_control::adjust_kernel_base(base)
+        let adjusted = bsp_impl::system_control::adjust_kernel_base(base);
+        debug_assert!(
+            adjusted.map_or(true, |base| is_aligned(base.addr(), BUILD_CONFIG.page_size)),
+            "Board {} violates the Kernel base alignment contract",
+            super::NAME
+        );
+
+        adjusted
+    }
+
+    /// Halts the system after an unrecoverable error.
+    ///
+    /// Before halting, the failure may be reported to the target
+    /// environment, e.g. as a nonzero exit status to an emulator.
+    pub fn halt_with_failure() -> ! {
+        bsp_impl::system_control::halt_with_failure()
     }
 }
@@ -3,3 +3,6 @@
 //! See [`crate::board`] for details.
 
 pub mod system_control;
+
+/// The name of this board.
+pub const NAME: &str = "qemu";
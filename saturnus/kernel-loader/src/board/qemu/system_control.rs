@@ -1,3 +1,12 @@
 pub fn adjust_kernel_base(base: *mut u8) -> Option<*mut u8> {
     None
 }
+
+pub fn halt_with_failure() -> ! {
+    // Exit the semihosting session with a failure status.
+    semihosting::debug::exit(semihosting::debug::EXIT_FAILURE);
+
+    // Halt the loader in an infinite loop in case debuggers
+    // decide to ignore our shutdown request.
+    loop {}
+}
@@ -2,9 +2,9 @@
 #![no_std]
 #![no_main]
 
-use core::{mem::size_of, ptr};
+use core::ptr;
 
-use libkern::BUILD_CONFIG;
+use libkern::{layout::KernelLayout, BUILD_CONFIG};
 use utils::align::is_aligned;
 
 mod arch;
@@ -12,77 +12,44 @@ mod board;
 mod panic;
 mod reloc;
 
-/// The layout of Kernel binary sections in memory.
-///
-/// This is supplied by the Kernel itself when it calls Loader.
-#[derive(Clone, Debug)]
-#[repr(C)]
-pub struct KernelLayout {
-    /// The start offset of the kernel's `.text` section.
-    text_start: u32,
-    /// The end offset of the kernel's `.text` section.
-    text_end: u32,
-    /// The start offset of the kernel's `.rodata` section.
-    rodata_start: u32,
-    /// The end offset of the kernel's `.rodata` section.
-    rodata_end: u32,
-    /// The start offset of the kernel's `.data` section.
-    data_start: u32,
-    /// The end offset of the kernel's `.data` section.
-    data_end: u32,
-    /// The start offset of the kernel's `.bss` section.
-    bss_start: u32,
-    /// The end offset of the kernel's `.bss` section.
-    bss_end: u32,
-    /// The end offset of the kernel binary.
-    kernel_end: u32,
-    /// The start offset of the kernel's `_DYNAMIC` array.
-    dynamic_start: u32,
+// Both symbols are section-relative, so they resolve to the actual
+// location of the Kernel Loader in memory.
+extern "C" {
+    static __saturnus_start: u8;
+    static __bss_end__: u8;
 }
 
-const _: () = assert!(size_of::<KernelLayout>() == 0x28);
-
 #[no_mangle]
 unsafe extern "C" fn main(
     kernel_base: *mut u8,
     kernel_layout: *const KernelLayout,
     ini1_base: *const u8,
 ) -> *const u8 {
+    // Everything we do from here on assumes the EL1 system registers
+    // to be in charge, so make sure we're actually running under EL1.
+    assert_eq!(arch::current_el(), 1, "Kernel Loader must run under EL1");
+
     // Relocate the Kernel in physical memory, if necessary.
     let (kernel_base, kernel_layout) = relocate_kernel_physically(kernel_base, kernel_layout);
     let kernel_layout = &*kernel_layout;
 
     // Validate the kernel layout.
-    assert!(is_aligned(
-        kernel_layout.text_start as _,
-        BUILD_CONFIG.page_size
-    ));
-    assert!(is_aligned(
-        kernel_layout.text_end as _,
-        BUILD_CONFIG.page_size
-    ));
-    assert!(is_aligned(
-        kernel_layout.rodata_start as _,
-        BUILD_CONFIG.page_size
-    ));
-    assert!(is_aligned(
-        kernel_layout.rodata_end as _,
-        BUILD_CONFIG.page_size
-    ));
-    assert!(is_aligned(
-        kernel_layout.data_start as _,
-        BUILD_CONFIG.page_size
-    ));
-    assert!(is_aligned(
-        kernel_layout.bss_end as _,
-        BUILD_CONFIG.page_size
-    ));
+    let layout = kernel_layout.address_space_layout();
+    if let Err(e) = layout.validate(BUILD_CONFIG.page_size) {
+        panic!("Kernel layout is invalid: {e:?}");
+    }
 
     todo!()
 }
 
 /// Performs physical relocation of the Kernel in memory.
 ///
+/// # Panics
+///
+/// Panics when the board adjusts the Kernel base to an address
+/// which is not page-aligned, or when the relocated Kernel would
+/// overlap the memory of the Kernel Loader itself.
+///
 /// # Safety
 ///
 /// - `kernel_base` must be non-zero, well-aligned and a pointer
@@ -96,6 +63,27 @@ unsafe fn relocate_kernel_physically(
 ) -> (*mut u8, *const KernelLayout) {
     match board::system_control::adjust_kernel_base(kernel_base) {
         Some(adjusted_kernel_base) => {
+            // Make sure the kernel is placed where it can be mapped,
+            // before anything is moved.
+            let kernel_start = adjusted_kernel_base.addr();
+            assert!(
+                is_aligned(kernel_start, BUILD_CONFIG.page_size),
+                "Board {} adjusted the Kernel base to unaligned address {kernel_start:#X}",
+                board::NAME
+            );
+
+            // Make sure the kernel does not end up overwriting us. Its
+            // footprint includes the zero-initialized `.bss` section.
+            let kernel_size = (*kernel_layout).kernel_end.max((*kernel_layout).bss_end);
+            let kernel_end = kernel_start + kernel_size as usize;
+            let loader_start = ptr::addr_of!(__saturnus_start).addr();
+            let loader_end = ptr::addr_of!(__bss_end__).addr();
+            assert!(
+                kernel_end <= loader_start || loader_end <= kernel_start,
+                "Relocated Kernel at {kernel_start:#X}..{kernel_end:#X} overlaps \
+                 Kernel Loader at {loader_start:#X}..{loader_end:#X}"
+            );
+
             // Move the kernel to the newly determined base in memory.
             // SAFETY: MMU is disabled; accessing physical memory is safe.
             // The other contracts are upheld by the caller.
@@ -2,22 +2,19 @@
 
 use core::panic::PanicInfo;
 
+use crate::board;
+
 #[panic_handler]
 fn panic(info: &PanicInfo<'_>) -> ! {
     // When building for debug...
     #[cfg(debug_assertions)]
     {
         // ...print the panic information to HOST stderr when in QEMU.
+        // Panics from exception handlers include the fault details.
         #[cfg(feature = "qemu")]
-        semihosting::heprintln!("{}", info);
+        semihosting::heprintln!("{}\nSP: {:#018x}", info, crate::arch::stack_pointer());
     }
 
-    // Exit the semihosting session when in QEMU.
-    #[cfg(feature = "qemu")]
-    semihosting::debug::exit(semihosting::debug::EXIT_FAILURE);
-
-    // Halt the loader in an infinite loop.
-    // This is official release behavior and is also useful when
-    // debuggers decide to ignore our shutdown requests in QEMU.
-    loop {}
+    // Report the failure to the target environment and halt.
+    board::system_control::halt_with_failure()
 }
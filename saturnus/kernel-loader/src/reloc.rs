@@ -1,10 +1,34 @@
-use core::slice;
+use core::{ptr, slice};
 
 use goblin::elf64::{
     dynamic::{self, Dyn, DynamicInfo},
     program_header::{self as ph, ProgramHeader},
     reloc::{self, Rel, Rela},
 };
+use utils::mem;
+
+extern "C" {
+    static __saturnus_start: u8;
+}
+
+// A pointer to the start of the loader which must be fixed up by
+// relocation. Checking it afterwards catches silently broken results.
+static RELOCATION_SENTINEL: &u8 = unsafe { &__saturnus_start };
+
+/// The outcome of [`apply_relocations`].
+///
+/// This is passed back to the startup code in a register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(usize)]
+pub enum RelocationResult {
+    /// All relocations were applied successfully.
+    Ok = 0,
+    /// A relocation of an unsupported type was encountered.
+    UnsupportedRelocation,
+    /// Relocations were applied, but a known pointer does not
+    /// resolve to its expected address afterwards.
+    SanityCheckFailed,
+}
 
 // This does not correspond to real program headers. We just need it
 // for `goblin` to translate addresses. A real base must be supplied
@@ -47,12 +71,12 @@ unsafe fn count_dynamic_entries<'d>(section_start: *const u8) -> &'d [Dyn] {
 ///   - The values have sufficient lifetime.
 ///   - The memory will not be mutated in this scope.
 ///
-/// # Panics
-///
-/// Panics when any relocations other than architecture-relative
-/// ones are encountered.
+/// Only architecture-relative relocations are supported; the
+/// function bails with [`RelocationResult::UnsupportedRelocation`]
+/// on encountering anything else. Panicking is not an option here
+/// since the panic machinery itself depends on relocated pointers.
 #[no_mangle]
-pub unsafe extern "C" fn apply_relocations(base: *mut u8, dynamic: *const u8) {
+pub unsafe extern "C" fn apply_relocations(base: *mut u8, dynamic: *const u8) -> RelocationResult {
     debug_assert!(dynamic > base as *const u8);
 
     // Extract all the relocations from the `.dynamic` section.
@@ -75,11 +99,11 @@ pub unsafe extern "C" fn apply_relocations(base: *mut u8, dynamic: *const u8) {
         match reloc::r_type(rel.r_info) {
             #[cfg(target_arch = "aarch64")]
             reloc::R_AARCH64_RELATIVE => {
-                let ptr = base.add(rel.r_offset as usize).cast::<usize>();
-                ptr.write(ptr.read() + base.addr());
+                let ptr = base.add(rel.r_offset as usize);
+                mem::write_unaligned(ptr, mem::read_unaligned::<usize>(ptr) + base.addr());
             }
 
-            _ => panic!("Unsupported relocation type encountered"),
+            _ => return RelocationResult::UnsupportedRelocation,
         }
     }
 
@@ -96,12 +120,49 @@ pub unsafe extern "C" fn apply_relocations(base: *mut u8, dynamic: *const u8) {
             #[cfg(target_arch = "aarch64")]
             reloc::R_AARCH64_RELATIVE => {
                 let value = base.offset(rela.r_addend as isize) as usize;
-                base.add(rela.r_offset as usize)
-                    .cast::<usize>()
-                    .write(value);
+                mem::write_unaligned(base.add(rela.r_offset as usize), value);
             }
 
-            _ => panic!("Unsupported relocation type encountered"),
+            _ => return RelocationResult::UnsupportedRelocation,
+        }
+    }
+
+    // Make sure a known pointer now points to where it should.
+    // SAFETY: The sentinel is a valid, aligned static. A volatile
+    // read keeps the compiler from folding it into an `adr`.
+    let sentinel: *const u8 = ptr::read_volatile(ptr::addr_of!(RELOCATION_SENTINEL));
+    if sentinel.addr() != base.addr() {
+        return RelocationResult::SanityCheckFailed;
+    }
+
+    RelocationResult::Ok
+}
+
+/// Reports a failed [`apply_relocations`] call and halts the
+/// loader.
+///
+/// This is the target of a branch from the startup code and
+/// must not rely on any relocated data, so formatting is out.
+#[no_mangle]
+pub extern "C" fn report_relocation_failure(result: RelocationResult) -> ! {
+    #[cfg(feature = "qemu")]
+    {
+        let reason = match result {
+            RelocationResult::Ok => "relocation succeeded\n",
+            RelocationResult::UnsupportedRelocation => "unsupported relocation type encountered\n",
+            RelocationResult::SanityCheckFailed => "sentinel pointer was not relocated\n",
+        };
+
+        if let Ok(mut stderr) = semihosting::host::HostStream::stderr() {
+            let _ = stderr.write_all(b"Kernel Loader relocation failed: ");
+            let _ = stderr.write_all(reason.as_bytes());
         }
+
+        semihosting::debug::exit(semihosting::debug::EXIT_FAILURE);
     }
+
+    #[cfg(not(feature = "qemu"))]
+    let _ = result;
+
+    loop {}
 }
@@ -0,0 +1,19 @@
+//! Identification of the CPU core the Kernel is executing on.
+
+use cortex_a::registers::MIDR_EL1;
+pub use libkern::cpu::CpuInfo;
+use tock_registers::interfaces::Readable;
+
+/// Reads the identification of the current CPU core.
+///
+/// On big.LITTLE systems, cores of different types are combined
+/// in a single system, so this must be called on the core in
+/// question.
+///
+/// # Note
+///
+/// This function does not make use of the stack.
+#[inline(always)]
+pub fn read_cpu_info() -> CpuInfo {
+    CpuInfo::from_midr(MIDR_EL1.get())
+}
@@ -3,4 +3,5 @@
 //! Everything is re-exported by the unifying
 //! [`crate::arch`] module.
 
+pub mod cpu;
 pub mod r0;
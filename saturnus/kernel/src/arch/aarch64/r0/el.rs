@@ -15,13 +15,11 @@
 
 use core::arch::asm;
 
-use cortex_a::registers::{ACTLR_EL2, ELR_EL2, HCR_EL2, MIDR_EL1, SPSR_EL2};
-use tock_registers::interfaces::{Readable, Writeable};
+use cortex_a::registers::{ACTLR_EL2, ELR_EL2, HCR_EL2, SPSR_EL2};
+use tock_registers::interfaces::Writeable;
 
 use super::cache::flush_entire_data_cache_and_invalidate_tlb;
-
-const PARTNUM_CORTEX_A53: u64 = 0xD03;
-const PARTNUM_CORTEX_A57: u64 = 0xD07;
+use crate::arch::cpu::read_cpu_info;
 
 /// Handles the execution of the Kernel under EL2.
 ///
@@ -82,14 +80,11 @@ extern "C" fn handle_running_under_el3() -> ! {
 }
 
 unsafe extern "C" fn prepare_el2_to_el1_transition(ret_addr: u64) {
-    let midr = MIDR_EL1.extract();
+    let cpu = read_cpu_info();
 
     // Check if we're running on Cortex-A53 or Cortex-A57 processors and
     // configure implementation-defined registers if that's the case.
-    if midr.matches_all(MIDR_EL1::Implementer::Arm)
-        && (midr.read(MIDR_EL1::PartNum) == PARTNUM_CORTEX_A57
-            || midr.read(MIDR_EL1::PartNum) == PARTNUM_CORTEX_A53)
-    {
+    if cpu.is_a53() || cpu.is_a57() {
         // TODO: Proper bitfield.
         //  - CPUACTLR access control = SET
         //  - CPUECTLR access control = SET